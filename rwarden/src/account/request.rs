@@ -1,8 +1,13 @@
-use crate::crypto::{KdfType, MasterPasswordHash, SymmetricEncryptedBytes};
-use crate::{account::Account, cache::Cache, util::ResponseExt, Client, Error, Request};
+use crate::crypto::{Kdf, MasterPasswordHash, SymmetricEncryptedBytes};
+use crate::{
+    account::Account, cache::Cache, util::ResponseExt, Client, Error, Request, RequestResponseError,
+};
+use chrono::{DateTime, Utc};
 use futures_core::future::BoxFuture;
 use reqwest::Method;
 use serde::Serialize;
+use serde_json::json;
+use std::time::{Duration, UNIX_EPOCH};
 use typed_builder::TypedBuilder;
 
 /// A [`Request`] for retrieving an account.
@@ -11,7 +16,7 @@ pub struct Get;
 
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
     type Output = BoxFuture<'request, crate::Result<Account, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -24,7 +29,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_account(&value)
                 .await
                 .map_err(Error::Cache)?;
@@ -49,7 +55,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for ModifyProfile
 {
     type Output = BoxFuture<'request, crate::Result<Account, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -63,7 +69,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_account(&value)
                 .await
                 .map_err(Error::Cache)?;
@@ -73,7 +80,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
 }
 
 /// A [`Request`] for modifying the email address of an account.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
 #[serde(rename_all = "PascalCase")]
 pub struct ModifyEmail {
     #[builder(setter(into))]
@@ -85,80 +92,204 @@ pub struct ModifyEmail {
     pub key: SymmetricEncryptedBytes,
 }
 
+impl ModifyEmail {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<(), RequestResponseError> {
+        client
+            .request(
+                Method::PUT,
+                format!("{}/accounts/email", client.urls().base),
+            )
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+}
+
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
     for ModifyEmail
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
-        Box::pin(async move {
-            client
-                .request(
-                    Method::PUT,
-                    format!("{}/accounts/email", client.urls().base),
-                )
-                .await?
-                .json(self)
-                .send()
-                .await?
-                .parse_empty()
-                .await?;
-            Ok(())
-        })
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
     }
 }
 
 /// A [`Request`] for modifying the password of an account.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
 #[serde(rename_all = "PascalCase")]
 pub struct ModifyPassword {
     pub master_password_hash: MasterPasswordHash,
     pub new_master_password_hash: MasterPasswordHash,
+    #[builder(default, setter(into, strip_option))]
+    pub master_password_hint: Option<String>,
     pub key: SymmetricEncryptedBytes,
 }
 
+impl ModifyPassword {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<(), RequestResponseError> {
+        client
+            .request(
+                Method::PUT,
+                format!("{}/accounts/password", client.urls().base),
+            )
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+}
+
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
     for ModifyPassword
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for modifying the KDF of an account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct ModifyKdf {
+    #[serde(flatten)]
+    pub kdf: Kdf,
+    pub master_password_hash: MasterPasswordHash,
+    pub new_master_password_hash: MasterPasswordHash,
+    pub key: SymmetricEncryptedBytes,
+}
+
+impl ModifyKdf {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<(), RequestResponseError> {
+        client
+            .request(Method::PUT, format!("{}/accounts/kdf", client.urls().base))
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for ModifyKdf
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for setting the key pair of an account that does not have one yet.
+///
+/// [`Client::generate_key_pair`] can be used for generating and setting a new key pair.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetKeys {
+    /// The base64 encoded public key.
+    #[builder(setter(into))]
+    pub public_key: String,
+    /// The private key that is encrypted with the symmetric key.
+    pub encrypted_private_key: SymmetricEncryptedBytes,
+}
+
+impl SetKeys {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<(), RequestResponseError> {
+        client
+            .request(
+                Method::POST,
+                format!("{}/accounts/keys", client.urls().base),
+            )
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for SetKeys
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for deleting an account.
+///
+/// The cache is cleared if the account was deleted.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct Delete {
+    pub master_password_hash: MasterPasswordHash,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Delete
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
-                .request(
-                    Method::PUT,
-                    format!("{}/accounts/password", client.urls().base),
-                )
+                .request(Method::DELETE, format!("{}/accounts", client.urls().base))
                 .await?
-                .json(self)
+                .json(&json!({
+                    "MasterPasswordHash": self.master_password_hash,
+                }))
                 .send()
                 .await?
                 .parse_empty()
                 .await?;
+            client.cache().await.clear().await.map_err(Error::Cache)?;
             Ok(())
         })
     }
 }
 
-/// A [`Request`] for modifying the KDF type and iterations of an account.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
-#[serde(rename_all = "PascalCase")]
-pub struct ModifyKdf {
-    pub kdf_type: KdfType,
-    pub kdf_iterations: u32,
+/// A [`Request`] for resetting the security stamp of an account.
+///
+/// This signs out all sessions of the account, including the session of the client that sends
+/// this request.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct ResetSecurityStamp {
     pub master_password_hash: MasterPasswordHash,
-    pub new_master_password_hash: MasterPasswordHash,
-    pub key: SymmetricEncryptedBytes,
 }
 
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
-    for ModifyKdf
+    for ResetSecurityStamp
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
-                .request(Method::PUT, format!("{}/accounts/kdf", client.urls().base))
+                .request(
+                    Method::POST,
+                    format!("{}/accounts/security-stamp", client.urls().base),
+                )
                 .await?
-                .json(self)
+                .json(&json!({
+                    "MasterPasswordHash": self.master_password_hash,
+                }))
                 .send()
                 .await?
                 .parse_empty()
@@ -167,3 +298,29 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
         })
     }
 }
+
+/// A [`Request`] for retrieving the date when the vault of an account was last modified.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GetRevisionDate;
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetRevisionDate
+{
+    type Output = BoxFuture<'request, crate::Result<DateTime<Utc>, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            // The revision date is returned as the number of milliseconds since the Unix epoch
+            let value: u64 = client
+                .request(
+                    Method::GET,
+                    format!("{}/accounts/revision-date", client.urls().base),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok((UNIX_EPOCH + Duration::from_millis(value)).into())
+        })
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     account::Account, cipher::CipherDetails, collection::CollectionDetails, folder::Folder,
-    settings::Domains, sync::Sync,
+    policy::Policy, send, settings::Domains, sync::Sync,
 };
 use async_trait::async_trait;
 use std::error::Error;
@@ -17,6 +17,8 @@ pub use json_file::{JsonFileCache, JsonFileCacheData};
 pub use memory::MemoryCache;
 
 /// A trait for storing resources offline.
+///
+/// The methods for policies and sends do nothing by default.
 #[async_trait]
 pub trait Cache {
     type Error: Error + Send;
@@ -45,6 +47,32 @@ pub trait Cache {
     where
         I: IntoIterator<Item = Uuid> + Send;
 
+    async fn save_policies<'a, I>(&mut self, _values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a Policy> + Send,
+    {
+        Ok(())
+    }
+    async fn delete_policies<I>(&mut self, _ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        Ok(())
+    }
+
+    async fn save_sends<'a, I>(&mut self, _values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a send::Send> + Send,
+    {
+        Ok(())
+    }
+    async fn delete_sends<I>(&mut self, _ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        Ok(())
+    }
+
     async fn save_domains<'a>(&mut self, value: &'a Domains) -> Result<(), Self::Error>;
     async fn delete_domains(&mut self) -> Result<(), Self::Error>;
 
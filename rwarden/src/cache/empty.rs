@@ -1,6 +1,6 @@
 use crate::{
     account::Account, cache::Cache, cipher::CipherDetails, collection::CollectionDetails,
-    folder::Folder, settings::Domains, sync::Sync,
+    folder::Folder, policy::Policy, send, settings::Domains, sync::Sync,
 };
 use async_trait::async_trait;
 use std::convert::Infallible;
@@ -64,6 +64,34 @@ impl Cache for EmptyCache {
         Ok(())
     }
 
+    async fn save_policies<'a, I>(&mut self, _values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a Policy> + Send,
+    {
+        Ok(())
+    }
+
+    async fn delete_policies<I>(&mut self, _ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        Ok(())
+    }
+
+    async fn save_sends<'a, I>(&mut self, _values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a send::Send> + Send,
+    {
+        Ok(())
+    }
+
+    async fn delete_sends<I>(&mut self, _ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        Ok(())
+    }
+
     async fn save_domains<'a>(&mut self, _value: &'a Domains) -> Result<(), Self::Error> {
         Ok(())
     }
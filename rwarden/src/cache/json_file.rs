@@ -1,6 +1,6 @@
 use crate::{
     account::Account, cache::Cache, cipher::CipherDetails, collection::CollectionDetails,
-    folder::Folder, settings::Domains, sync::Sync,
+    folder::Folder, policy::Policy, send, settings::Domains, sync::Sync,
 };
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -17,8 +17,10 @@ pub struct JsonFileCacheData {
     pub folders: Vec<Folder>,
     pub collections: Vec<CollectionDetails>,
     pub ciphers: Vec<CipherDetails>,
-    // pub policies: Vec<Policy>,
-    // pub sends: Vec<Send>,
+    #[serde(default)]
+    pub policies: Vec<Policy>,
+    #[serde(default)]
+    pub sends: Vec<send::Send>,
     pub domains: Option<Domains>,
 }
 
@@ -29,6 +31,8 @@ impl JsonFileCacheData {
             folders: value.folders,
             collections: value.collections,
             ciphers: value.ciphers,
+            policies: value.policies,
+            sends: value.sends,
             domains: Some(value.domains),
         }
     }
@@ -163,6 +167,44 @@ impl Cache for JsonFileCache {
         .await
     }
 
+    async fn save_policies<'a, I>(&mut self, values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a Policy> + Send,
+    {
+        self.modify_data(|data| data.policies.extend(values.into_iter().cloned()))
+            .await
+    }
+
+    async fn delete_policies<I>(&mut self, ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        self.modify_data(|data| {
+            let ids = ids.into_iter().collect::<HashSet<_>>();
+            data.policies.retain(|v| !ids.contains(&v.id));
+        })
+        .await
+    }
+
+    async fn save_sends<'a, I>(&mut self, values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a send::Send> + Send,
+    {
+        self.modify_data(|data| data.sends.extend(values.into_iter().cloned()))
+            .await
+    }
+
+    async fn delete_sends<I>(&mut self, ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        self.modify_data(|data| {
+            let ids = ids.into_iter().collect::<HashSet<_>>();
+            data.sends.retain(|v| !ids.contains(&v.id));
+        })
+        .await
+    }
+
     async fn save_domains<'a>(
         &mut self,
         value: &'a crate::settings::Domains,
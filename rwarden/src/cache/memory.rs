@@ -1,6 +1,6 @@
 use crate::{
     account::Account, cache::Cache, cipher::CipherDetails, collection::CollectionDetails,
-    folder::Folder, settings::Domains, sync::Sync,
+    folder::Folder, policy::Policy, send, settings::Domains, sync::Sync,
 };
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -14,8 +14,10 @@ pub struct MemoryCache {
     pub folders: Vec<Folder>,
     pub collections: Vec<CollectionDetails>,
     pub ciphers: Vec<CipherDetails>,
-    // pub policies: Vec<Policy>,
-    // pub sends: Vec<Send>,
+    #[serde(default)]
+    pub policies: Vec<Policy>,
+    #[serde(default)]
+    pub sends: Vec<send::Send>,
     pub domains: Option<Domains>,
 }
 
@@ -84,6 +86,40 @@ impl Cache for MemoryCache {
         Ok(())
     }
 
+    async fn save_policies<'a, I>(&mut self, values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a Policy> + Send,
+    {
+        self.policies.extend(values.into_iter().cloned());
+        Ok(())
+    }
+
+    async fn delete_policies<I>(&mut self, ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        self.policies.retain(|v| !ids.contains(&v.id));
+        Ok(())
+    }
+
+    async fn save_sends<'a, I>(&mut self, values: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = &'a send::Send> + Send,
+    {
+        self.sends.extend(values.into_iter().cloned());
+        Ok(())
+    }
+
+    async fn delete_sends<I>(&mut self, ids: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Uuid> + Send,
+    {
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        self.sends.retain(|v| !ids.contains(&v.id));
+        Ok(())
+    }
+
     async fn save_domains<'a>(&mut self, value: &'a Domains) -> Result<(), Self::Error> {
         self.domains = Some(value.clone());
         Ok(())
@@ -99,6 +135,8 @@ impl Cache for MemoryCache {
         self.folders = value.folders.clone();
         self.collections = value.collections.clone();
         self.ciphers = value.ciphers.clone();
+        self.policies = value.policies.clone();
+        self.sends = value.sends.clone();
         self.domains = Some(value.domains.clone());
         Ok(())
     }
@@ -1,25 +1,79 @@
 //! Module for cipher resources.
 
-use crate::crypto::{SymmetricEncryptedBytes, SymmetricEncryptedString};
-use crate::util;
+use crate::crypto::{
+    symmetric_encryption::DecryptionError, Decrypt, SymmetricEncryptedBytes,
+    SymmetricEncryptedString, SymmetricEncryption, SymmetricKey,
+};
+use crate::{util, AttachmentDecryptionError, FileUploadType, KeyRing, KeyRingDecryptionError};
 use chrono::{DateTime, FixedOffset};
 use derive_setters::Setters;
-use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, DeserializeOwned};
+use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 use serde_repr::{Deserialize_repr as DeserializeRepr, Serialize_repr as SerializeRepr};
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
 use uuid::Uuid;
+use zeroize::Zeroize;
 
 pub use request::*;
+pub use view::*;
 
 mod request;
+mod view;
 
 /// The type of a custom field.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeRepr, SerializeRepr)]
-#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FieldType {
-    Text = 0,
-    Hidden = 1,
-    Boolean = 2,
+    Text,
+    Hidden,
+    Boolean,
+    /// A field that refers to a property of the cipher (see [`Field::linked_id`]).
+    Linked,
+    /// A field type that is not supported by this library.
+    Unknown(u8),
+}
+
+impl From<u8> for FieldType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Text,
+            1 => Self::Hidden,
+            2 => Self::Boolean,
+            3 => Self::Linked,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+impl From<FieldType> for u8 {
+    fn from(value: FieldType) -> Self {
+        match value {
+            FieldType::Text => 0,
+            FieldType::Hidden => 1,
+            FieldType::Boolean => 2,
+            FieldType::Linked => 3,
+            FieldType::Unknown(v) => v,
+        }
+    }
+}
+
+impl Serialize for FieldType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8((*self).into())
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u8::deserialize(deserializer).map(Self::from)
+    }
 }
 
 /// A custom field of a cipher.
@@ -33,6 +87,8 @@ pub struct Field {
     pub name: Option<SymmetricEncryptedString>,
     /// The value of the field.
     pub value: Option<SymmetricEncryptedString>,
+    /// The ID of the property that a [linked](FieldType::Linked) field refers to.
+    pub linked_id: Option<u32>,
 }
 
 /// Entry in the password history.
@@ -52,7 +108,7 @@ pub struct AttachmentRequest {
     /// The file name of the attachment.
     pub file_name: SymmetricEncryptedString,
     /// The key of the attachment.
-    pub key: SymmetricEncryptedBytes,
+    pub key: Option<SymmetricEncryptedBytes>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Setters, Serialize)]
@@ -70,6 +126,10 @@ pub struct RequestModel {
     pub fields: Vec<Field>,
     pub favorite: bool,
     pub password_history: Vec<PasswordHistoryEntry>,
+    /// The file names and keys of the existing attachments.
+    ///
+    /// The server only reads the keys of attachments from the `Attachments2` field.
+    #[serde(rename = "Attachments2")]
     pub attachments: HashMap<Uuid, AttachmentRequest>,
     pub last_known_revision_date: Option<DateTime<FixedOffset>>,
 }
@@ -152,12 +212,33 @@ impl Owner {
 
 /// The type of a cipher.
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     Login(Login),
     Card(Card),
     Identity(Identity),
     SecureNote,
+    /// A cipher type that is not supported by this library.
+    ///
+    /// The fields contain the raw JSON of the type specific data, they are serialized unchanged.
+    Unknown {
+        ty: i32,
+        fields: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl Hash for Type {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Self::Login(v) => v.hash(state),
+            Self::Card(v) => v.hash(state),
+            Self::Identity(v) => v.hash(state),
+            Self::SecureNote => {}
+            // `serde_json::Value` does not implement `Hash`, so the fields are left out
+            Self::Unknown { ty, .. } => ty.hash(state),
+        }
+    }
 }
 
 impl Serialize for Type {
@@ -165,26 +246,32 @@ impl Serialize for Type {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Type", 2)?;
+        let mut state = serializer.serialize_map(None)?;
         match self {
             Self::Login(v) => {
-                state.serialize_field("Type", &1)?;
-                state.serialize_field("Login", &v)?;
+                state.serialize_entry("Type", &1)?;
+                state.serialize_entry("Login", &v)?;
             }
             Self::SecureNote => {
-                state.serialize_field("Type", &2)?;
+                state.serialize_entry("Type", &2)?;
                 let v = SecureNote {
                     ty: SecureNoteType::Generic,
                 };
-                state.serialize_field("SecureNote", &v)?;
+                state.serialize_entry("SecureNote", &v)?;
             }
             Self::Card(v) => {
-                state.serialize_field("Type", &3)?;
-                state.serialize_field("Card", &v)?;
+                state.serialize_entry("Type", &3)?;
+                state.serialize_entry("Card", &v)?;
             }
             Self::Identity(v) => {
-                state.serialize_field("Type", &4)?;
-                state.serialize_field("Identity", &v)?;
+                state.serialize_entry("Type", &4)?;
+                state.serialize_entry("Identity", &v)?;
+            }
+            Self::Unknown { ty, fields } => {
+                state.serialize_entry("Type", ty)?;
+                for (key, value) in fields {
+                    state.serialize_entry(key, value)?;
+                }
             }
         }
         state.end()
@@ -196,46 +283,47 @@ impl<'de> Deserialize<'de> for Type {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "PascalCase")]
-        struct Inner {
-            #[serde(rename = "Type")]
-            ty: i32,
-            login: Option<Login>,
-            card: Option<Card>,
-            identity: Option<Identity>,
-            secure_note: Option<SecureNote>,
+        fn take<T, E>(
+            fields: &mut serde_json::Map<String, serde_json::Value>,
+            key: &str,
+        ) -> Result<T, E>
+        where
+            T: DeserializeOwned,
+            E: de::Error,
+        {
+            let value = fields.remove(key).unwrap_or(serde_json::Value::Null);
+            T::deserialize(value).map_err(de::Error::custom)
         }
 
-        let v = Inner::deserialize(deserializer)?;
-        match v.ty {
+        // The fields are deserialized into a map first, so that the raw JSON of unknown types can
+        // be kept
+        let mut fields = serde_json::Map::deserialize(deserializer)?;
+        let ty: i32 = match fields.remove("Type") {
+            Some(v) => i32::deserialize(v).map_err(de::Error::custom)?,
+            None => return Err(de::Error::missing_field("Type")),
+        };
+        match ty {
             1 => {
-                let v = v
-                    .login
+                let v = take::<Option<Login>, _>(&mut fields, "Login")?
                     .ok_or_else(|| de::Error::custom("Login must not be null"))?;
                 Ok(Self::Login(v))
             }
             2 => {
-                v.secure_note
+                take::<Option<SecureNote>, _>(&mut fields, "SecureNote")?
                     .ok_or_else(|| de::Error::custom("SecureNote must not be null"))?;
                 Ok(Self::SecureNote)
             }
             3 => {
-                let v = v
-                    .card
+                let v = take::<Option<Card>, _>(&mut fields, "Card")?
                     .ok_or_else(|| de::Error::custom("Card must not be null"))?;
                 Ok(Self::Card(v))
             }
             4 => {
-                let v = v
-                    .identity
+                let v = take::<Option<Identity>, _>(&mut fields, "Identity")?
                     .ok_or_else(|| de::Error::custom("Identity must not be null"))?;
                 Ok(Self::Identity(v))
             }
-            _ => Err(de::Error::invalid_value(
-                de::Unexpected::Signed(v.ty.into()),
-                &"one of `1`, `2`, `3`, or `4`",
-            )),
+            _ => Ok(Self::Unknown { ty, fields }),
         }
     }
 }
@@ -358,13 +446,70 @@ enum SecureNoteType {
 #[serde(rename_all = "PascalCase")]
 pub struct Attachment {
     pub id: Uuid,
+    /// The URL for downloading the encrypted data of the attachment.
+    ///
+    /// The URL expires after some time, a new one can be retrieved with [`GetAttachment`].
     pub url: String,
     pub file_name: SymmetricEncryptedString,
-    pub key: SymmetricEncryptedBytes,
+    /// The key that the data of the attachment is encrypted with.
+    ///
+    /// The key is encrypted with the key of the owner of the cipher. Attachments that were
+    /// created by old clients have no key, their data is encrypted with the key of the owner
+    /// directly (see [`Client::rekey_attachment`]).
+    ///
+    /// [`Client::rekey_attachment`]: crate::Client::rekey_attachment
+    pub key: Option<SymmetricEncryptedBytes>,
     pub size: String,
     pub size_name: String,
 }
 
+impl Attachment {
+    /// Decrypts the key of the attachment with the key of the owner of the cipher.
+    ///
+    /// For attachments without a key, the key of the owner is returned.
+    pub fn decrypt_key(
+        &self,
+        key: &SymmetricKey,
+    ) -> Result<SymmetricKey, AttachmentDecryptionError> {
+        match &self.key {
+            Some(v) => {
+                let mut bytes = v.decrypt(key).map_err(AttachmentDecryptionError::Key)?;
+                let attachment_key = SymmetricKey::from_bytes(&bytes)
+                    .map_err(|_| AttachmentDecryptionError::InvalidKeyLength);
+                bytes.zeroize();
+                attachment_key
+            }
+            None => Ok(key.clone()),
+        }
+    }
+
+    /// Decrypts the downloaded data of the attachment with the key of the owner of the cipher.
+    pub fn decrypt_data(
+        &self,
+        key: &SymmetricKey,
+        data: &[u8],
+    ) -> Result<Vec<u8>, AttachmentDecryptionError> {
+        let attachment_key = self.decrypt_key(key)?;
+        SymmetricEncryption::from_bytes(data)?
+            .decrypt(&attachment_key)
+            .map_err(AttachmentDecryptionError::Decryption)
+    }
+}
+
+/// The location where the data of a new attachment is uploaded to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AttachmentUpload {
+    pub attachment_id: Uuid,
+    pub url: String,
+    pub file_upload_type: FileUploadType,
+    /// The cipher including the new attachment.
+    ///
+    /// This is `None` if the upload URL was renewed.
+    #[serde(rename = "CipherResponse")]
+    pub cipher: Option<Cipher>,
+}
+
 // https://github.com/bitwarden/server/blob/v1.40.0/src/Core/Models/Api/Response/CipherResponseModel.cs
 /// A cipher resource.
 // NOTE: Serialize is only needed for cache
@@ -392,6 +537,93 @@ pub struct Cipher {
     pub view_password: bool,
 }
 
+impl Cipher {
+    /// Decrypts the cipher with the key of its owner.
+    ///
+    /// This is the key of the organization if the cipher is owned by an organization and the key
+    /// of the user otherwise.
+    pub fn decrypt_with(&self, key_ring: &KeyRing) -> Result<CipherView, KeyRingDecryptionError> {
+        Ok(self.decrypt(key_ring.key(self.organization_id)?)?)
+    }
+
+    /// Encrypts all encrypted values of the cipher (including the password history and the keys
+    /// of the attachments) with a new key.
+    pub(crate) fn reencrypt(
+        &mut self,
+        old_key: &SymmetricKey,
+        new_key: &SymmetricKey,
+    ) -> Result<(), DecryptionError> {
+        let mut values = vec![&mut self.name];
+        values.extend(self.notes.as_mut());
+        for field in &mut self.fields {
+            values.extend(field.name.as_mut());
+            values.extend(field.value.as_mut());
+        }
+        for entry in &mut self.password_history {
+            values.push(&mut entry.password);
+        }
+        match &mut self.ty {
+            Type::Login(v) => {
+                values.extend(v.username.as_mut());
+                values.extend(v.password.as_mut());
+                values.extend(v.totp.as_mut());
+                values.extend(v.uris.iter_mut().map(|v| &mut v.uri));
+            }
+            Type::Card(v) => {
+                let card_values = vec![
+                    &mut v.cardholder_name,
+                    &mut v.brand,
+                    &mut v.number,
+                    &mut v.expiration_month,
+                    &mut v.expiration_year,
+                    &mut v.code,
+                ];
+                values.extend(card_values.into_iter().flatten());
+            }
+            Type::Identity(v) => {
+                let identity_values = vec![
+                    &mut v.title,
+                    &mut v.first_name,
+                    &mut v.middle_name,
+                    &mut v.last_name,
+                    &mut v.address_1,
+                    &mut v.address_2,
+                    &mut v.address_3,
+                    &mut v.city,
+                    &mut v.state,
+                    &mut v.postal_code,
+                    &mut v.country,
+                    &mut v.company,
+                    &mut v.email,
+                    &mut v.phone,
+                    &mut v.ssn,
+                    &mut v.username,
+                    &mut v.passport_number,
+                    &mut v.license_number,
+                ];
+                values.extend(identity_values.into_iter().flatten());
+            }
+            Type::SecureNote => {}
+            // Rotating the key is rejected before if a cipher has an unknown type
+            Type::Unknown { .. } => {}
+        }
+        for attachment in &mut self.attachments {
+            values.push(&mut attachment.file_name);
+        }
+        for value in values {
+            util::reencrypt(&mut value.0, old_key, new_key)?;
+        }
+        // The attachment files are encrypted with the attachment keys, so only the keys need to
+        // be encrypted again
+        for attachment in &mut self.attachments {
+            if let Some(key) = &mut attachment.key {
+                util::reencrypt(key, old_key, new_key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A cipher resource with additional information.
 // NOTE: Serialize is only needed for cache
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
@@ -402,3 +634,59 @@ pub struct CipherDetails {
     #[serde(deserialize_with = "util::deserialize_optional")]
     pub collection_ids: Vec<Uuid>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{SymmetricEncryptedString, SymmetricKey};
+
+    #[test]
+    fn unknown_type() {
+        let key = SymmetricKey::generate();
+        let name = SymmetricEncryptedString::encrypt("foo", &key);
+        let value = serde_json::json!({
+            "Id": "2c6b4b4c-5c32-4e05-9f9f-0d0d2d7ad2a1",
+            "FolderId": null,
+            "OrganizationId": null,
+            "Name": name,
+            "Type": 5,
+            "Login": null,
+            "SshKey": { "PrivateKey": name, "KeyFingerprint": name },
+            "Notes": null,
+            "Fields": [
+                { "Type": 3, "Name": name, "Value": null, "LinkedId": 100 },
+                { "Type": 9, "Name": name, "Value": name, "LinkedId": null },
+            ],
+            "Attachments": null,
+            "OrganizationUseTotp": false,
+            "PasswordHistory": null,
+            "RevisionDate": "2021-09-01T12:00:00.000000Z",
+            "DeletedDate": null,
+            "Favorite": false,
+            "Edit": true,
+            "ViewPassword": true,
+        });
+        let cipher: Cipher = serde_json::from_value(value.clone()).unwrap();
+        match &cipher.ty {
+            Type::Unknown { ty, fields } => {
+                assert_eq!(*ty, 5);
+                assert_eq!(fields["SshKey"], value["SshKey"]);
+            }
+            _ => panic!("cipher type is not unknown"),
+        }
+        assert_eq!(cipher.fields[0].ty, FieldType::Linked);
+        assert_eq!(cipher.fields[0].linked_id, Some(100));
+        assert_eq!(cipher.fields[1].ty, FieldType::Unknown(9));
+
+        let serialized = serde_json::to_value(&cipher).unwrap();
+        assert_eq!(serialized["Type"], value["Type"]);
+        assert_eq!(serialized["SshKey"], value["SshKey"]);
+        assert_eq!(serialized["Fields"], value["Fields"]);
+        let deserialized: Cipher = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized, cipher);
+
+        let view = cipher.decrypt(&key).unwrap();
+        assert_eq!(view.name, "foo");
+        assert_eq!(view.ty.encrypt(&key), cipher.ty);
+    }
+}
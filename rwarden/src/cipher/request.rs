@@ -1,8 +1,9 @@
-use crate::cipher::{Cipher, CipherDetails, Owner, RequestModel};
+use crate::cipher::{Attachment, AttachmentUpload, Cipher, CipherDetails, Owner, RequestModel};
+use crate::crypto::{MasterPasswordHash, SymmetricEncryptedBytes, SymmetricEncryptedString};
 use crate::util::ResponseExt;
-use crate::{cache::Cache, crypto::MasterPasswordHash, Client, Error, Request};
+use crate::{cache::Cache, Client, Error, FileUploadType, Request, RequestResponseError};
 use futures_core::{future::BoxFuture, stream::BoxStream};
-use reqwest::Method;
+use reqwest::{Body, Method};
 use serde::Serialize;
 use serde_json::json;
 use std::collections::HashMap;
@@ -17,7 +18,7 @@ pub struct Get {
 
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
     type Output = BoxFuture<'request, crate::Result<Cipher, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -45,7 +46,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Create
 {
     type Output = BoxFuture<'request, crate::Result<Cipher, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         #[derive(Serialize)]
         #[serde(rename_all = "PascalCase")]
         struct Request<'a> {
@@ -87,7 +88,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Delete
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let (method, path) = if self.soft_delete {
                 (Method::PUT, format!("ciphers/{}/delete", self.id))
@@ -102,7 +103,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse_empty()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .delete_ciphers(std::iter::once(self.id))
                 .await
                 .map_err(Error::Cache)?;
@@ -124,7 +126,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for BulkDelete
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let (method, path) = if self.soft_delete {
                 (Method::PUT, "ciphers/delete")
@@ -143,7 +145,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse_empty()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .delete_ciphers(self.ids.iter().copied())
                 .await
                 .map_err(Error::Cache)?;
@@ -172,7 +175,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Modify
 {
     type Output = BoxFuture<'request, crate::Result<Cipher, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -203,7 +206,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for ModifyPartial
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
                 .request(
@@ -235,7 +238,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for ModifyCollections
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
                 .request(
@@ -265,7 +268,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Restore
 {
     type Output = BoxFuture<'request, crate::Result<Cipher, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -292,7 +295,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for BulkRestore
 {
     type Output = BoxStream<'request, crate::Result<Vec<Cipher>, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         request_stream! {
             client
                 .request(Method::PUT, format!("{}/ciphers/restore", client.urls().base))
@@ -315,7 +318,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Share
 {
     type Output = BoxFuture<'request, crate::Result<Cipher, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         #[derive(Serialize)]
         #[serde(rename_all = "PascalCase")]
         struct Request<'a> {
@@ -354,7 +357,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for BulkShare
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         #[derive(Serialize)]
         #[serde(rename_all = "PascalCase")]
         struct RequestModelWithId<'a> {
@@ -403,7 +406,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for BulkMove
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
                 .request(Method::PUT, format!("{}/ciphers/move", client.urls().base))
@@ -422,7 +425,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
 }
 
 /// A [`Request`] for deleting all ciphers.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
 pub struct Purge {
     pub master_password_hash: MasterPasswordHash,
     #[builder(default, setter(strip_option))]
@@ -433,7 +436,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Purge
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let mut request = client
                 .request(
@@ -464,7 +467,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetDetails
 {
     type Output = BoxFuture<'request, crate::Result<CipherDetails, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -477,7 +480,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_ciphers(std::iter::once(&value))
                 .await
                 .map_err(Error::Cache)?;
@@ -494,14 +498,272 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetAllDetails
 {
     type Output = BoxStream<'request, crate::Result<Vec<CipherDetails>, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         request_stream! {
             client.request(Method::GET, format!("{}/ciphers", client.urls().base)).await?,
             response => client
-                .cache_mut()
+                .cache()
+                .await
                 .save_ciphers(&response.data)
                 .await
                 .map_err(Error::Cache)?
         }
     }
 }
+
+/// A [`Request`] for creating an attachment.
+///
+/// An upload slot for the attachment is requested first and the data is uploaded afterwards. If
+/// the upload fails, the attachment is deleted again.
+///
+/// The file name and the key must be encrypted with the key of the owner of the cipher and the
+/// data must be encrypted with the key of the attachment (see [`Client::create_attachment`]).
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct CreateAttachment {
+    pub cipher_id: Uuid,
+    pub file_name: SymmetricEncryptedString,
+    pub key: SymmetricEncryptedBytes,
+    /// The encrypted data in the binary format of encrypted files.
+    pub data: Vec<u8>,
+}
+
+impl CreateAttachment {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<Cipher, RequestResponseError> {
+        let body = self.data.clone().into();
+        let length = self.data.len() as u64;
+        create_attachment(
+            client,
+            self.cipher_id,
+            &self.file_name,
+            &self.key,
+            body,
+            length,
+        )
+        .await
+    }
+}
+
+/// Creates an attachment and uploads the encrypted data.
+pub(crate) async fn create_attachment<TCache>(
+    client: &Client<TCache>,
+    cipher_id: Uuid,
+    file_name: &SymmetricEncryptedString,
+    key: &SymmetricEncryptedBytes,
+    body: Body,
+    length: u64,
+) -> Result<Cipher, RequestResponseError> {
+    let upload: AttachmentUpload = client
+        .request(
+            Method::POST,
+            format!("{}/ciphers/{}/attachment/v2", client.urls().base, cipher_id),
+        )
+        .await?
+        .json(&json!({
+            "Key": key,
+            "FileName": file_name,
+            "FileSize": length,
+            "AdminRequest": false,
+        }))
+        .send()
+        .await?
+        .parse()
+        .await?;
+    let result = client
+        .upload_file(
+            upload.file_upload_type,
+            format!(
+                "{}/ciphers/{}/attachment/{}",
+                client.urls().base,
+                cipher_id,
+                upload.attachment_id
+            ),
+            &upload.url,
+            file_name.to_string(),
+            body,
+            length,
+        )
+        .await;
+    if let Err(e) = result {
+        // The attachment is deleted so that no attachment without data remains, the error of the
+        // upload is more relevant than an error of the deletion
+        let _ = DeleteAttachment {
+            cipher_id,
+            attachment_id: upload.attachment_id,
+        }
+        .execute(client)
+        .await;
+        return Err(e);
+    }
+    match upload.cipher {
+        Some(v) => Ok(v),
+        None => Ok(client
+            .request(
+                Method::GET,
+                format!("{}/ciphers/{}", client.urls().base, cipher_id),
+            )
+            .await?
+            .send()
+            .await?
+            .parse()
+            .await?),
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for CreateAttachment
+{
+    type Output = BoxFuture<'request, crate::Result<Cipher, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for renewing the upload URL of an attachment.
+///
+/// This is needed if the URL returned by [`CreateAttachment`] expired before the data was uploaded
+/// with [`UploadAttachment`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct RenewAttachmentUpload {
+    pub cipher_id: Uuid,
+    pub attachment_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for RenewAttachmentUpload
+{
+    type Output = BoxFuture<'request, crate::Result<AttachmentUpload, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!(
+                        "{}/ciphers/{}/attachment/{}/renew",
+                        client.urls().base,
+                        self.cipher_id,
+                        self.attachment_id
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for uploading the data of an attachment.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct UploadAttachment {
+    pub cipher_id: Uuid,
+    pub attachment_id: Uuid,
+    /// The upload URL returned by the server.
+    pub url: String,
+    pub file_upload_type: FileUploadType,
+    pub file_name: SymmetricEncryptedString,
+    /// The encrypted data in the binary format of encrypted files.
+    pub data: Vec<u8>,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for UploadAttachment
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .upload_file(
+                    self.file_upload_type,
+                    format!(
+                        "{}/ciphers/{}/attachment/{}",
+                        client.urls().base,
+                        self.cipher_id,
+                        self.attachment_id
+                    ),
+                    &self.url,
+                    self.file_name.to_string(),
+                    self.data.clone().into(),
+                    self.data.len() as u64,
+                )
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for retrieving an attachment.
+///
+/// The download URL of the returned attachment is renewed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetAttachment {
+    pub cipher_id: Uuid,
+    pub attachment_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetAttachment
+{
+    type Output = BoxFuture<'request, crate::Result<Attachment, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!(
+                        "{}/ciphers/{}/attachment/{}",
+                        client.urls().base,
+                        self.cipher_id,
+                        self.attachment_id
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for deleting an attachment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct DeleteAttachment {
+    pub cipher_id: Uuid,
+    pub attachment_id: Uuid,
+}
+
+impl DeleteAttachment {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<(), RequestResponseError> {
+        client
+            .request(
+                Method::DELETE,
+                format!(
+                    "{}/ciphers/{}/attachment/{}",
+                    client.urls().base,
+                    self.cipher_id,
+                    self.attachment_id
+                ),
+            )
+            .await?
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for DeleteAttachment
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
@@ -0,0 +1,483 @@
+use crate::cipher::{
+    Attachment, Card, Cipher, Field, FieldType, Identity, Login, LoginUri, LoginUriMatchType,
+    PasswordHistoryEntry, Type,
+};
+use crate::crypto::{SymmetricEncryptedBytes, SymmetricEncryptedString, SymmetricKey};
+use crate::util::{decrypt_field, decrypt_optional_field, encrypt_optional};
+use crate::FieldDecryptionError;
+use chrono::{DateTime, FixedOffset};
+use derive_setters::Setters;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use uuid::Uuid;
+
+/// The decrypted counterpart of [`Field`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldView {
+    /// The type of the field.
+    pub ty: FieldType,
+    /// The name of the field.
+    pub name: Option<String>,
+    /// The value of the field.
+    pub value: Option<String>,
+    /// The ID of the property that a [linked](FieldType::Linked) field refers to.
+    pub linked_id: Option<u32>,
+}
+
+impl FieldView {
+    /// Encrypts the field with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Field {
+        Field {
+            ty: self.ty,
+            name: encrypt_optional(&self.name, key),
+            value: encrypt_optional(&self.value, key),
+            linked_id: self.linked_id,
+        }
+    }
+}
+
+impl Field {
+    fn decrypt(&self, key: &SymmetricKey, index: usize) -> Result<FieldView, FieldDecryptionError> {
+        Ok(FieldView {
+            ty: self.ty,
+            name: decrypt_optional_field(&self.name, key, || format!("fields[{}].name", index))?,
+            value: decrypt_optional_field(&self.value, key, || format!("fields[{}].value", index))?,
+            linked_id: self.linked_id,
+        })
+    }
+}
+
+/// The decrypted counterpart of [`PasswordHistoryEntry`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PasswordHistoryEntryView {
+    /// The password.
+    pub password: String,
+    /// The date when the password was last used.
+    pub last_used_date: Option<DateTime<FixedOffset>>,
+}
+
+impl PasswordHistoryEntryView {
+    /// Encrypts the entry with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> PasswordHistoryEntry {
+        PasswordHistoryEntry {
+            password: SymmetricEncryptedString::encrypt(&self.password, key),
+            last_used_date: self.last_used_date,
+        }
+    }
+}
+
+impl PasswordHistoryEntry {
+    fn decrypt(
+        &self,
+        key: &SymmetricKey,
+        index: usize,
+    ) -> Result<PasswordHistoryEntryView, FieldDecryptionError> {
+        Ok(PasswordHistoryEntryView {
+            password: decrypt_field(&self.password, key, || {
+                format!("password_history[{}].password", index)
+            })?,
+            last_used_date: self.last_used_date,
+        })
+    }
+}
+
+/// The decrypted counterpart of [`Attachment`].
+///
+/// The key of the attachment stays encrypted, it is only needed for decrypting the file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttachmentView {
+    pub id: Uuid,
+    pub url: String,
+    pub file_name: String,
+    pub key: Option<SymmetricEncryptedBytes>,
+    pub size: String,
+    pub size_name: String,
+}
+
+impl AttachmentView {
+    /// Encrypts the attachment with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Attachment {
+        Attachment {
+            id: self.id,
+            url: self.url.clone(),
+            file_name: SymmetricEncryptedString::encrypt(&self.file_name, key),
+            key: self.key.clone(),
+            size: self.size.clone(),
+            size_name: self.size_name.clone(),
+        }
+    }
+}
+
+impl Attachment {
+    fn decrypt(
+        &self,
+        key: &SymmetricKey,
+        index: usize,
+    ) -> Result<AttachmentView, FieldDecryptionError> {
+        Ok(AttachmentView {
+            id: self.id,
+            url: self.url.clone(),
+            file_name: decrypt_field(&self.file_name, key, || {
+                format!("attachments[{}].file_name", index)
+            })?,
+            key: self.key.clone(),
+            size: self.size.clone(),
+            size_name: self.size_name.clone(),
+        })
+    }
+}
+
+/// The decrypted counterpart of [`Type`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeView {
+    Login(LoginView),
+    Card(CardView),
+    Identity(IdentityView),
+    SecureNote,
+    /// A cipher type that is not supported by this library (see [`Type::Unknown`]).
+    ///
+    /// The data is kept as it is, because it is unknown which values are encrypted.
+    Unknown {
+        ty: i32,
+        fields: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl Hash for TypeView {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Self::Login(v) => v.hash(state),
+            Self::Card(v) => v.hash(state),
+            Self::Identity(v) => v.hash(state),
+            Self::SecureNote => {}
+            // `serde_json::Value` does not implement `Hash`, so the fields are left out
+            Self::Unknown { ty, .. } => ty.hash(state),
+        }
+    }
+}
+
+impl TypeView {
+    /// Encrypts the cipher type with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Type {
+        match self {
+            Self::Login(v) => Type::Login(v.encrypt(key)),
+            Self::Card(v) => Type::Card(v.encrypt(key)),
+            Self::Identity(v) => Type::Identity(v.encrypt(key)),
+            Self::SecureNote => Type::SecureNote,
+            Self::Unknown { ty, fields } => Type::Unknown {
+                ty: *ty,
+                fields: fields.clone(),
+            },
+        }
+    }
+}
+
+impl Type {
+    fn decrypt(&self, key: &SymmetricKey) -> Result<TypeView, FieldDecryptionError> {
+        Ok(match self {
+            Self::Login(v) => TypeView::Login(v.decrypt(key)?),
+            Self::Card(v) => TypeView::Card(v.decrypt(key)?),
+            Self::Identity(v) => TypeView::Identity(v.decrypt(key)?),
+            Self::SecureNote => TypeView::SecureNote,
+            Self::Unknown { ty, fields } => TypeView::Unknown {
+                ty: *ty,
+                fields: fields.clone(),
+            },
+        })
+    }
+}
+
+/// The decrypted counterpart of [`Login`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Setters)]
+#[setters(strip_option, prefix = "with_")]
+pub struct LoginView {
+    /// The username of the login cipher.
+    pub username: Option<String>,
+    /// The password of the login cipher.
+    pub password: Option<String>,
+    /// The authenticator key for the time-based one-time password.
+    pub totp: Option<String>,
+    /// The URIs of the login cipher.
+    pub uris: Vec<LoginUriView>,
+    /// The revision date of the login cipher.
+    pub password_revision_date: Option<DateTime<FixedOffset>>,
+}
+
+impl LoginView {
+    /// Creates a new [`LoginView`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encrypts the login with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Login {
+        Login {
+            username: encrypt_optional(&self.username, key),
+            password: encrypt_optional(&self.password, key),
+            totp: encrypt_optional(&self.totp, key),
+            uris: self.uris.iter().map(|v| v.encrypt(key)).collect(),
+            password_revision_date: self.password_revision_date,
+        }
+    }
+}
+
+impl Login {
+    fn decrypt(&self, key: &SymmetricKey) -> Result<LoginView, FieldDecryptionError> {
+        Ok(LoginView {
+            username: decrypt_optional_field(&self.username, key, || "login.username".to_owned())?,
+            password: decrypt_optional_field(&self.password, key, || "login.password".to_owned())?,
+            totp: decrypt_optional_field(&self.totp, key, || "login.totp".to_owned())?,
+            uris: self
+                .uris
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    Ok(LoginUriView {
+                        uri: decrypt_field(&v.uri, key, || format!("login.uris[{}].uri", i))?,
+                        match_type: v.match_type,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            password_revision_date: self.password_revision_date,
+        })
+    }
+}
+
+/// The decrypted counterpart of [`LoginUri`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoginUriView {
+    pub uri: String,
+    pub match_type: LoginUriMatchType,
+}
+
+impl LoginUriView {
+    /// Creates a new [`LoginUriView`].
+    pub fn new<S: Into<String>>(uri: S, match_type: LoginUriMatchType) -> Self {
+        Self {
+            uri: uri.into(),
+            match_type,
+        }
+    }
+
+    /// Encrypts the URI with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> LoginUri {
+        LoginUri {
+            uri: SymmetricEncryptedString::encrypt(&self.uri, key),
+            match_type: self.match_type,
+        }
+    }
+}
+
+/// The decrypted counterpart of [`Card`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Setters)]
+#[setters(strip_option, prefix = "with_")]
+pub struct CardView {
+    /// The name of the cardholder.
+    pub cardholder_name: Option<String>,
+    /// The brand of the card.
+    pub brand: Option<String>,
+    /// The card number.
+    pub number: Option<String>,
+    /// The expiration month of the card.
+    pub expiration_month: Option<String>,
+    /// The expiration year of the card.
+    pub expiration_year: Option<String>,
+    /// The security code of the card.
+    pub code: Option<String>,
+}
+
+impl CardView {
+    /// Encrypts the card with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Card {
+        Card {
+            cardholder_name: encrypt_optional(&self.cardholder_name, key),
+            brand: encrypt_optional(&self.brand, key),
+            number: encrypt_optional(&self.number, key),
+            expiration_month: encrypt_optional(&self.expiration_month, key),
+            expiration_year: encrypt_optional(&self.expiration_year, key),
+            code: encrypt_optional(&self.code, key),
+        }
+    }
+}
+
+impl Card {
+    fn decrypt(&self, key: &SymmetricKey) -> Result<CardView, FieldDecryptionError> {
+        let decrypt = |value, field: &str| decrypt_optional_field(value, key, || field.to_owned());
+        Ok(CardView {
+            cardholder_name: decrypt(&self.cardholder_name, "card.cardholder_name")?,
+            brand: decrypt(&self.brand, "card.brand")?,
+            number: decrypt(&self.number, "card.number")?,
+            expiration_month: decrypt(&self.expiration_month, "card.expiration_month")?,
+            expiration_year: decrypt(&self.expiration_year, "card.expiration_year")?,
+            code: decrypt(&self.code, "card.code")?,
+        })
+    }
+}
+
+/// The decrypted counterpart of [`Identity`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Setters)]
+#[setters(strip_option, prefix = "with_")]
+pub struct IdentityView {
+    pub title: Option<String>,
+    pub first_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub last_name: Option<String>,
+    pub address_1: Option<String>,
+    pub address_2: Option<String>,
+    pub address_3: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+    pub company: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub ssn: Option<String>,
+    pub username: Option<String>,
+    pub passport_number: Option<String>,
+    pub license_number: Option<String>,
+}
+
+impl IdentityView {
+    /// Encrypts the identity with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Identity {
+        Identity {
+            title: encrypt_optional(&self.title, key),
+            first_name: encrypt_optional(&self.first_name, key),
+            middle_name: encrypt_optional(&self.middle_name, key),
+            last_name: encrypt_optional(&self.last_name, key),
+            address_1: encrypt_optional(&self.address_1, key),
+            address_2: encrypt_optional(&self.address_2, key),
+            address_3: encrypt_optional(&self.address_3, key),
+            city: encrypt_optional(&self.city, key),
+            state: encrypt_optional(&self.state, key),
+            postal_code: encrypt_optional(&self.postal_code, key),
+            country: encrypt_optional(&self.country, key),
+            company: encrypt_optional(&self.company, key),
+            email: encrypt_optional(&self.email, key),
+            phone: encrypt_optional(&self.phone, key),
+            ssn: encrypt_optional(&self.ssn, key),
+            username: encrypt_optional(&self.username, key),
+            passport_number: encrypt_optional(&self.passport_number, key),
+            license_number: encrypt_optional(&self.license_number, key),
+        }
+    }
+}
+
+impl Identity {
+    fn decrypt(&self, key: &SymmetricKey) -> Result<IdentityView, FieldDecryptionError> {
+        let decrypt = |value, field: &str| decrypt_optional_field(value, key, || field.to_owned());
+        Ok(IdentityView {
+            title: decrypt(&self.title, "identity.title")?,
+            first_name: decrypt(&self.first_name, "identity.first_name")?,
+            middle_name: decrypt(&self.middle_name, "identity.middle_name")?,
+            last_name: decrypt(&self.last_name, "identity.last_name")?,
+            address_1: decrypt(&self.address_1, "identity.address_1")?,
+            address_2: decrypt(&self.address_2, "identity.address_2")?,
+            address_3: decrypt(&self.address_3, "identity.address_3")?,
+            city: decrypt(&self.city, "identity.city")?,
+            state: decrypt(&self.state, "identity.state")?,
+            postal_code: decrypt(&self.postal_code, "identity.postal_code")?,
+            country: decrypt(&self.country, "identity.country")?,
+            company: decrypt(&self.company, "identity.company")?,
+            email: decrypt(&self.email, "identity.email")?,
+            phone: decrypt(&self.phone, "identity.phone")?,
+            ssn: decrypt(&self.ssn, "identity.ssn")?,
+            username: decrypt(&self.username, "identity.username")?,
+            passport_number: decrypt(&self.passport_number, "identity.passport_number")?,
+            license_number: decrypt(&self.license_number, "identity.license_number")?,
+        })
+    }
+}
+
+/// The decrypted counterpart of [`Cipher`].
+///
+/// A view is created with [`Cipher::decrypt`] or [`Cipher::decrypt_with`]. The cipher can be
+/// modified by encrypting the view again and passing it to [`Modify::inherit`].
+///
+/// [`Modify::inherit`]: crate::cipher::Modify::inherit
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CipherView {
+    pub id: Uuid,
+    pub folder_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub name: String,
+    pub ty: TypeView,
+    pub notes: Option<String>,
+    pub fields: Vec<FieldView>,
+    pub attachments: Vec<AttachmentView>,
+    pub organization_use_totp: bool,
+    pub password_history: Vec<PasswordHistoryEntryView>,
+    pub revision_date: DateTime<FixedOffset>,
+    pub deleted_date: Option<DateTime<FixedOffset>>,
+    pub favorite: bool,
+    pub edit: bool,
+    pub view_password: bool,
+}
+
+impl CipherView {
+    /// Encrypts the cipher with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Cipher {
+        Cipher {
+            id: self.id,
+            folder_id: self.folder_id,
+            organization_id: self.organization_id,
+            name: SymmetricEncryptedString::encrypt(&self.name, key),
+            ty: self.ty.encrypt(key),
+            notes: encrypt_optional(&self.notes, key),
+            fields: self.fields.iter().map(|v| v.encrypt(key)).collect(),
+            attachments: self.attachments.iter().map(|v| v.encrypt(key)).collect(),
+            organization_use_totp: self.organization_use_totp,
+            password_history: self
+                .password_history
+                .iter()
+                .map(|v| v.encrypt(key))
+                .collect(),
+            revision_date: self.revision_date,
+            deleted_date: self.deleted_date,
+            favorite: self.favorite,
+            edit: self.edit,
+            view_password: self.view_password,
+        }
+    }
+}
+
+impl Cipher {
+    /// Decrypts the cipher with the given key.
+    pub fn decrypt(&self, key: &SymmetricKey) -> Result<CipherView, FieldDecryptionError> {
+        Ok(CipherView {
+            id: self.id,
+            folder_id: self.folder_id,
+            organization_id: self.organization_id,
+            name: decrypt_field(&self.name, key, || "name".to_owned())?,
+            ty: self.ty.decrypt(key)?,
+            notes: decrypt_optional_field(&self.notes, key, || "notes".to_owned())?,
+            fields: self
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v.decrypt(key, i))
+                .collect::<Result<_, _>>()?,
+            attachments: self
+                .attachments
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v.decrypt(key, i))
+                .collect::<Result<_, _>>()?,
+            organization_use_totp: self.organization_use_totp,
+            password_history: self
+                .password_history
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v.decrypt(key, i))
+                .collect::<Result<_, _>>()?,
+            revision_date: self.revision_date,
+            deleted_date: self.deleted_date,
+            favorite: self.favorite,
+            edit: self.edit,
+            view_password: self.view_password,
+        })
+    }
+}
@@ -1,111 +1,248 @@
 use crate::crypto::{
-    self, KdfType, MasterPasswordHash, SourceKey, SymmetricEncryptedBytes, SymmetricKey,
+    asymmetric_encryption::Rsa2048OaepSha1,
+    symmetric_encryption::{self, AesCbc256HmacSha256},
+    AsymmetricEncryptedBytes, GenericEncryptedBytes, Kdf, MasterPasswordHash, RsaKeyPair, SendKey,
+    SourceKey, SymmetricEncryptedBytes, SymmetricEncryptedString, SymmetricKey,
 };
+use crate::util::{self, ResponseExt, TokenResult};
 use crate::{
-    account, cache::Cache, util::ResponseExt, AccessTokenData, LoginData, LoginError,
-    PrivateKeyError, RegisterData, Request, RequestResponseError, Urls,
+    account, cache::Cache, cipher, organization, response, send, sync, AccessTokenData,
+    AccountKeyError, ApiKey, ApiKeyLoginData, AttachmentDecryptionError, AttachmentError,
+    FileUploadError, FileUploadType, HttpConfig, KeyError, KeyPairError, KeyRing, KeyRingError,
+    KeyRotationError, LoginData, LoginError, OrganizationKeyError, PrivateKeyError, RegisterData,
+    Request, RequestResponseError, RetryPolicy, Session, SessionObserver, TwoFactorProvider, Urls,
 };
-use reqwest::{header, IntoUrl, Method, RequestBuilder};
-use rsa::{pkcs8::FromPrivateKey, RsaPrivateKey};
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use reqwest::{header, multipart, IntoUrl, Method, Response, StatusCode};
+use rsa::pkcs8::{FromPrivateKey, FromPublicKey};
+use rsa::{PublicKeyParts, RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
-use std::time::{Duration, SystemTime};
+use std::io::{self, SeekFrom};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+use std::time::{Duration, Instant, SystemTime};
+use std::{collections::HashMap, fmt, mem};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{Mutex, MutexGuard};
+use tokio::task::JoinHandle;
+use tokio_util::io::ReaderStream;
 use typed_builder::TypedBuilder;
 use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
 
 #[derive(Deserialize)]
 struct Prelogin {
-    #[serde(rename = "Kdf")]
-    kdf_type: KdfType,
-    #[serde(rename = "KdfIterations")]
-    kdf_iterations: u32,
+    #[serde(flatten)]
+    kdf: Kdf,
 }
 
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
     access_token: String,
     expires_in: u64,
-    token_type: String,
-    refresh_token: String,
-    scope: String,
+    refresh_token: Option<String>,
     #[serde(rename = "Key")]
     key: SymmetricEncryptedBytes,
     #[serde(rename = "PrivateKey")]
     private_key: Option<SymmetricEncryptedBytes>,
-    #[serde(rename = "Kdf")]
-    kdf_type: KdfType,
-    #[serde(rename = "KdfIterations")]
-    kdf_iterations: u32,
-    #[serde(rename = "ResetMasterPassword")]
-    reset_master_password: bool,
+    #[serde(flatten)]
+    kdf: Kdf,
+    #[serde(rename = "TwoFactorToken")]
+    two_factor_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RefreshTokenResponse {
+    access_token: String,
+    expires_in: u64,
+    refresh_token: Option<String>,
+}
+
+/// Credentials used for retrieving new access tokens.
+///
+/// The refresh token is zeroized when the credentials are dropped.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Credentials {
+    /// A refresh token that was returned by a login with the master password.
+    RefreshToken(#[serde(with = "util::zeroizing")] Zeroizing<String>),
+    /// A personal API key.
+    ApiKey(ApiKey),
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RefreshToken(_) => f.write_str("RefreshToken(<redacted>)"),
+            Self::ApiKey(v) => f.debug_tuple("ApiKey").field(v).finish(),
+        }
+    }
+}
+
+impl From<String> for Credentials {
+    fn from(refresh_token: String) -> Self {
+        Self::RefreshToken(Zeroizing::new(refresh_token))
+    }
+}
+
+impl From<&str> for Credentials {
+    fn from(refresh_token: &str) -> Self {
+        Self::RefreshToken(Zeroizing::new(refresh_token.to_owned()))
+    }
+}
+
+impl From<ApiKey> for Credentials {
+    fn from(api_key: ApiKey) -> Self {
+        Self::ApiKey(api_key)
+    }
+}
+
+fn api_key_form(api_key: &ApiKey) -> HashMap<&'static str, String> {
+    let mut req = HashMap::new();
+    req.insert("grant_type", "client_credentials".to_owned());
+    req.insert("client_id", api_key.client_id.clone());
+    req.insert("client_secret", api_key.client_secret.to_string());
+    req.insert("scope", "api".to_owned());
+    req.insert("DeviceIdentifier", api_key.device_identifier.clone());
+    if let Some(v) = &api_key.device_name {
+        req.insert("DeviceName", v.clone());
+    }
+    if let Some(v) = api_key.device_type {
+        req.insert("DeviceType", (v as u8).to_string());
+    }
+    req
 }
 
 /// Result of successful login.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct LoginResponse<TCache> {
     pub client: Client<TCache>,
     pub access_token_data: AccessTokenData,
-    pub refresh_token: String,
+    /// The refresh token. This is `None` for logins with an API key.
+    pub refresh_token: Option<String>,
     pub key: SymmetricEncryptedBytes,
     pub private_key: Option<SymmetricEncryptedBytes>,
-    pub kdf_type: KdfType,
-    pub kdf_iterations: u32,
+    pub kdf: Kdf,
+    /// The token that can be used for skipping two factor authentication on later logins.
+    ///
+    /// This is only returned if the login was performed with [`LoginData::two_factor_remember`]
+    /// set to `true`. See [`LoginData::with_two_factor_remember_token`].
+    pub two_factor_remember_token: Option<String>,
 }
 
-/// A client used for logging in and registering users.
+impl<TCache: fmt::Debug> fmt::Debug for LoginResponse<TCache> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginResponse")
+            .field("client", &self.client)
+            .field("access_token_data", &self.access_token_data)
+            .field(
+                "refresh_token",
+                &self.refresh_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("key", &self.key)
+            .field("private_key", &self.private_key)
+            .field("kdf", &self.kdf)
+            .field(
+                "two_factor_remember_token",
+                &self
+                    .two_factor_remember_token
+                    .as_ref()
+                    .map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// A login that requires two factor authentication.
+///
+/// This is returned in [`LoginError::TwoFactorRequired`] and keeps the derived keys, so that
+/// the login can be continued without deriving them again.
 #[derive(Debug, Clone)]
-pub struct AnonymousClient {
-    urls: Urls,
-    client: reqwest::Client,
+pub struct PendingTwoFactorLogin {
+    client: AnonymousClient,
+    data: LoginData,
+    source_key: SourceKey,
+    master_password_hash: MasterPasswordHash,
+    device_identifier: String,
+    two_factor_providers: Vec<response::TwoFactorProvider>,
 }
 
-impl AnonymousClient {
-    pub fn new(urls: Urls) -> Self {
-        Self {
-            urls,
-            client: reqwest::Client::new(),
-        }
+impl PendingTwoFactorLogin {
+    /// Returns the providers that can be used for two factor authentication.
+    pub fn two_factor_providers(&self) -> &[response::TwoFactorProvider] {
+        &self.two_factor_providers
     }
 
-    pub fn urls(&self) -> &Urls {
-        &self.urls
+    /// Returns the source key.
+    pub fn source_key(&self) -> &SourceKey {
+        &self.source_key
+    }
+
+    /// Returns the master password hash.
+    pub fn master_password_hash(&self) -> &MasterPasswordHash {
+        &self.master_password_hash
     }
 
-    async fn prelogin(&self, email: &str) -> Result<Prelogin, LoginError> {
+    /// Returns the identifier of the device that is used for the login.
+    pub fn device_identifier(&self) -> &str {
+        &self.device_identifier
+    }
+
+    /// Sends a two factor authentication token to the email address of the user.
+    ///
+    /// This can be used if [`response::TwoFactorProvider::Email`] is one of the available
+    /// providers.
+    pub async fn send_email(&self) -> Result<(), RequestResponseError> {
         self.client
+            .http_client
             .request(
                 Method::POST,
-                format!("{}/accounts/prelogin", self.urls.base),
+                format!("{}/two-factor/send-email-login", self.client.urls.base),
             )
-            .json(&json!({ "email": email }))
+            .json(&json!({
+                "Email": self.data.email,
+                "MasterPasswordHash": self.master_password_hash,
+                "DeviceIdentifier": self.device_identifier,
+            }))
             .send()
             .await?
-            .parse_with_login_result()
+            .parse_empty()
             .await
     }
 
-    pub async fn login<TCache: Cache>(
-        self,
-        data: &LoginData,
+    /// Continues the login with a two factor authentication token.
+    ///
+    /// If `remember` is `true`, the server returns a token in
+    /// [`LoginResponse::two_factor_remember_token`] that can be used for skipping two factor
+    /// authentication on later logins. The pending login is not consumed, so it can be continued
+    /// again if the token was invalid.
+    pub async fn continue_with<TCache, S>(
+        &self,
+        provider: TwoFactorProvider,
+        token: S,
+        remember: bool,
         cache: TCache,
-    ) -> Result<LoginResponse<TCache>, LoginError> {
-        let Prelogin {
-            kdf_type,
-            kdf_iterations,
-        } = self.prelogin(&data.email).await?;
-        let source_key = SourceKey::new(&data.email, &data.password, kdf_type, kdf_iterations);
-        let master_password_hash = MasterPasswordHash::new(&source_key, &data.password, kdf_type);
+    ) -> Result<LoginResponse<TCache>, LoginError>
+    where
+        TCache: Cache,
+        S: Into<String>,
+    {
+        let mut pending = self.clone();
+        pending.data.two_factor_provider = Some(provider);
+        pending.data.two_factor_token = Some(Zeroizing::new(token.into()));
+        pending.data.two_factor_remember = remember;
+        pending.send(cache).await
+    }
 
+    async fn send<TCache: Cache>(self, cache: TCache) -> Result<LoginResponse<TCache>, LoginError> {
+        let data = &self.data;
         let mut req = HashMap::new();
         req.insert("grant_type", "password");
         req.insert("username", &data.email);
-        let master_password_hash = master_password_hash.to_string();
+        let master_password_hash = self.master_password_hash.to_string();
         req.insert("password", &master_password_hash);
         req.insert("client_id", &data.client_id);
         req.insert("scope", "api offline_access");
-        let device_identifier = Uuid::new_v4().to_hyphenated().to_string();
-        req.insert("DeviceIdentifier", &device_identifier);
+        req.insert("DeviceIdentifier", &self.device_identifier);
         if let Some(v) = &data.device_name {
             req.insert("DeviceName", v);
         }
@@ -116,7 +253,7 @@ impl AnonymousClient {
         if let Some(v) = &data.device_push_token {
             req.insert("DevicePushToken", v);
         }
-        let two_factor_provider = data.two_factor_provider.map(|v| (v as u8).to_string());
+        let two_factor_provider = data.two_factor_provider.map(|v| u8::from(v).to_string());
         if let Some(v) = &two_factor_provider {
             req.insert("TwoFactorProvider", v);
         }
@@ -127,45 +264,344 @@ impl AnonymousClient {
             req.insert("TwoFactorRemember", "1");
         }
 
-        let token = self
+        let result = self
             .client
-            .request(Method::POST, self.urls.auth.clone())
+            .http_client
+            .request(Method::POST, self.client.urls.auth.clone())
             .form(&req)
             .send()
             .await?
-            .parse_with_login_result::<TokenResponse>()
+            .parse_token::<TokenResponse>()
             .await?;
-        let access_token_data = AccessTokenData {
-            access_token: token.access_token,
-            expiry_time: SystemTime::now() + Duration::from_secs(token.expires_in),
+        let token = match result {
+            TokenResult::Success(v) => v,
+            TokenResult::TwoFactorRequired(two_factor_providers) => {
+                return Err(LoginError::TwoFactorRequired {
+                    two_factor_providers: two_factor_providers.clone(),
+                    pending: Box::new(Self {
+                        two_factor_providers,
+                        ..self
+                    }),
+                })
+            }
+        };
+        let credentials = match &token.refresh_token {
+            Some(v) => Credentials::from(v.as_str()),
+            None => return Err(LoginError::MissingRefreshToken),
         };
-        let client = Client {
-            client: self.client,
+        Ok(self.client.into_login_response(
+            data.email.clone(),
+            token,
+            self.source_key,
+            credentials,
             cache,
-            urls: self.urls,
+        ))
+    }
+}
+
+/// A client used for logging in and registering users.
+#[derive(Debug, Clone)]
+pub struct AnonymousClient {
+    urls: Urls,
+    http_client: reqwest::Client,
+}
+
+impl AnonymousClient {
+    /// Creates a new [`AnonymousClient`] with the default HTTP configuration.
+    pub fn new(urls: Urls) -> Self {
+        Self::with_http_client(urls, reqwest::Client::new())
+    }
+
+    /// Creates a new [`AnonymousClient`] with the given HTTP configuration.
+    pub fn with_http_config(urls: Urls, http_config: &HttpConfig) -> reqwest::Result<Self> {
+        Ok(Self::with_http_client(urls, http_config.build()?))
+    }
+
+    /// Creates a new [`AnonymousClient`] that uses the given HTTP client.
+    ///
+    /// The HTTP client is also used by the [`Client`] that is returned when logging in.
+    pub fn with_http_client(urls: Urls, http_client: reqwest::Client) -> Self {
+        Self { urls, http_client }
+    }
+
+    pub fn urls(&self) -> &Urls {
+        &self.urls
+    }
+
+    /// Returns the HTTP client.
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    /// Sends the master password hint of the account to its email address.
+    pub async fn request_password_hint<S: AsRef<str>>(
+        &self,
+        email: S,
+    ) -> Result<(), RequestResponseError> {
+        self.http_client
+            .request(
+                Method::POST,
+                format!("{}/accounts/password-hint", self.urls.base),
+            )
+            .json(&json!({ "Email": email.as_ref() }))
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+
+    /// Sends an email with a token to the email address of the account that can be used to
+    /// delete the account without the master password.
+    ///
+    /// To delete the account with the token, [`AnonymousClient::delete_account_with_token`] can
+    /// be used.
+    pub async fn request_account_deletion<S: AsRef<str>>(
+        &self,
+        email: S,
+    ) -> Result<(), RequestResponseError> {
+        self.http_client
+            .request(
+                Method::POST,
+                format!("{}/accounts/delete-recover", self.urls.base),
+            )
+            .json(&json!({ "Email": email.as_ref() }))
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+
+    /// Deletes an account with the token that was sent with
+    /// [`AnonymousClient::request_account_deletion`].
+    pub async fn delete_account_with_token<S: AsRef<str>>(
+        &self,
+        user_id: Uuid,
+        token: S,
+    ) -> Result<(), RequestResponseError> {
+        self.http_client
+            .request(
+                Method::POST,
+                format!("{}/accounts/delete-recover-token", self.urls.base),
+            )
+            .json(&json!({ "UserId": user_id, "Token": token.as_ref() }))
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+
+    /// Disables two factor authentication of an account with the recovery code.
+    ///
+    /// The recovery code can be retrieved with [`two_factor::GetRecoveryCode`].
+    ///
+    /// [`two_factor::GetRecoveryCode`]: crate::two_factor::GetRecoveryCode
+    pub async fn recover_two_factor<S1, S2, S3>(
+        &self,
+        email: S1,
+        password: S2,
+        recovery_code: S3,
+    ) -> Result<(), RequestResponseError>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        S3: AsRef<str>,
+    {
+        let email = email.as_ref();
+        let password = password.as_ref();
+        let Prelogin { kdf } = self.prelogin(email).await?;
+        let source_key = SourceKey::derive(email, password, &kdf).await;
+        let master_password_hash =
+            MasterPasswordHash::derive(&source_key, password, kdf.kdf_type()).await;
+        self.http_client
+            .request(
+                Method::POST,
+                format!("{}/two-factor/recover", self.urls.base),
+            )
+            .json(&json!({
+                "Email": email,
+                "MasterPasswordHash": master_password_hash,
+                "RecoveryCode": recovery_code.as_ref(),
+            }))
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+
+    /// Accesses a send with its access ID.
+    ///
+    /// If the send is protected by a password, the password hash must be given (see
+    /// [`SendKey::hash_password`]). The data of the send can be decrypted with the key that is
+    /// derived from the key material in the URL of the send (see [`SendKey::decode`] and
+    /// [`SendKey::stretch`]).
+    ///
+    /// [`SendKey::hash_password`]: crate::crypto::SendKey::hash_password
+    /// [`SendKey::decode`]: crate::crypto::SendKey::decode
+    /// [`SendKey::stretch`]: crate::crypto::SendKey::stretch
+    pub async fn access_send<S: AsRef<str>>(
+        &self,
+        access_id: S,
+        password_hash: Option<&str>,
+    ) -> Result<send::SendAccess, RequestResponseError> {
+        self.http_client
+            .request(
+                Method::POST,
+                format!("{}/sends/access/{}", self.urls.base, access_id.as_ref()),
+            )
+            .json(&json!({ "Password": password_hash }))
+            .send()
+            .await?
+            .parse()
+            .await
+    }
+
+    /// Returns the URL for downloading the encrypted file of a send.
+    ///
+    /// The ID of the send and the ID of the file are contained in the [`SendAccess`] that is
+    /// returned by [`AnonymousClient::access_send`].
+    ///
+    /// [`SendAccess`]: send::SendAccess
+    pub async fn access_send_file<S1, S2>(
+        &self,
+        send_id: S1,
+        file_id: S2,
+        password_hash: Option<&str>,
+    ) -> Result<String, RequestResponseError>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Response {
+            url: String,
+        }
+
+        let response: Response = self
+            .http_client
+            .request(
+                Method::POST,
+                format!(
+                    "{}/sends/{}/access/file/{}",
+                    self.urls.base,
+                    send_id.as_ref(),
+                    file_id.as_ref()
+                ),
+            )
+            .json(&json!({ "Password": password_hash }))
+            .send()
+            .await?
+            .parse()
+            .await?;
+        Ok(response.url)
+    }
+
+    async fn prelogin(&self, email: &str) -> Result<Prelogin, RequestResponseError> {
+        self.http_client
+            .request(
+                Method::POST,
+                format!("{}/accounts/prelogin", self.urls.base),
+            )
+            .json(&json!({ "email": email }))
+            .send()
+            .await?
+            .parse()
+            .await
+    }
+
+    /// Logs in with the master password.
+    ///
+    /// If two factor authentication is required, [`LoginError::TwoFactorRequired`] is returned.
+    /// The login can then be continued with the [`PendingTwoFactorLogin`] that is contained in the
+    /// error.
+    pub async fn login<TCache: Cache>(
+        self,
+        data: &LoginData,
+        cache: TCache,
+    ) -> Result<LoginResponse<TCache>, LoginError> {
+        let Prelogin { kdf } = self.prelogin(&data.email).await?;
+        let source_key = SourceKey::derive(&data.email, data.password.as_str(), &kdf).await;
+        let master_password_hash =
+            MasterPasswordHash::derive(&source_key, data.password.as_str(), kdf.kdf_type()).await;
+        let pending = PendingTwoFactorLogin {
+            client: self,
+            data: data.clone(),
             source_key,
-            encrypted_symmetric_key: token.key.clone(),
-            encrypted_private_key: token.private_key.clone(),
-            refresh_token: token.refresh_token.clone(),
-            access_token_data: Some(access_token_data.clone()),
+            master_password_hash,
+            device_identifier: Uuid::new_v4().to_hyphenated().to_string(),
+            two_factor_providers: Vec::new(),
         };
-        Ok(LoginResponse {
+        pending.send(cache).await
+    }
+
+    /// Logs in with a personal API key.
+    ///
+    /// The API key is used for authenticating with the server, so two factor authentication is
+    /// not required. The master password is only used for deriving the source key.
+    pub async fn login_with_api_key<TCache: Cache>(
+        self,
+        data: &ApiKeyLoginData,
+        cache: TCache,
+    ) -> Result<LoginResponse<TCache>, LoginError> {
+        let token = self
+            .http_client
+            .request(Method::POST, self.urls.auth.clone())
+            .form(&api_key_form(&data.api_key))
+            .send()
+            .await?
+            .parse::<TokenResponse>()
+            .await?;
+        let Prelogin { kdf } = self.prelogin(&data.email).await?;
+        let source_key = SourceKey::derive(&data.email, data.password.as_str(), &kdf).await;
+        // Make sure that the master password is correct
+        SymmetricKey::new(&source_key, &token.key)?;
+        let credentials = Credentials::ApiKey(data.api_key.clone());
+        Ok(self.into_login_response(data.email.clone(), token, source_key, credentials, cache))
+    }
+
+    fn into_login_response<TCache>(
+        self,
+        email: String,
+        token: TokenResponse,
+        source_key: SourceKey,
+        credentials: Credentials,
+        cache: TCache,
+    ) -> LoginResponse<TCache> {
+        let access_token_data = AccessTokenData {
+            access_token: Zeroizing::new(token.access_token),
+            expiry_time: SystemTime::now() + Duration::from_secs(token.expires_in),
+        };
+        let client = Client::builder()
+            .http_client(self.http_client)
+            .cache(cache)
+            .urls(self.urls)
+            .email(email)
+            .kdf(token.kdf)
+            .source_key(Some(source_key))
+            .encrypted_symmetric_key(token.key.clone())
+            .encrypted_private_key(token.private_key.clone())
+            .credentials(credentials)
+            .access_token_data(Some(access_token_data.clone()))
+            .build();
+        LoginResponse {
             client,
             access_token_data,
             refresh_token: token.refresh_token,
             key: token.key,
             private_key: token.private_key,
-            kdf_type: token.kdf_type,
-            kdf_iterations: token.kdf_iterations,
-        })
+            kdf: token.kdf,
+            two_factor_remember_token: token.two_factor_token,
+        }
     }
 
     pub async fn register(&self, data: &RegisterData) -> Result<(), RequestResponseError> {
-        let kdf_iterations = data.kdf_iterations.unwrap_or(100_000);
-        let kdf_type = data.kdf_type.unwrap_or(KdfType::Pbkdf2Sha256);
-        let source_key = SourceKey::new(&data.email, &data.password, kdf_type, kdf_iterations);
-        let master_password_hash = MasterPasswordHash::new(&source_key, &data.password, kdf_type);
-        let protected_symmetric_key = crypto::generate_protected_symmetric_key(&source_key);
+        let kdf = data.kdf.unwrap_or_else(|| Kdf::pbkdf2_sha256(100_000));
+        let source_key = SourceKey::derive(&data.email, data.password.as_str(), &kdf).await;
+        let master_password_hash =
+            MasterPasswordHash::derive(&source_key, data.password.as_str(), kdf.kdf_type()).await;
+        let symmetric_key = SymmetricKey::generate();
+        let protected_symmetric_key = symmetric_key.protect(&source_key);
+        let key_pair = RsaKeyPair::generate_async().await;
 
         let req = json!({
             "Email": data.email,
@@ -174,11 +610,17 @@ impl AnonymousClient {
             "Key": protected_symmetric_key.to_string(),
             "Name": data.name,
             "OrganizationUserId": data.organization_user_id,
-            "Kdf": data.kdf_type,
-            "KdfIterations": data.kdf_iterations,
+            "Kdf": kdf.kdf_type(),
+            "KdfIterations": kdf.iterations(),
+            "KdfMemory": kdf.memory(),
+            "KdfParallelism": kdf.parallelism(),
+            "Keys": {
+                "PublicKey": key_pair.encode_public_key(),
+                "EncryptedPrivateKey": key_pair.protect(&symmetric_key),
+            },
         });
 
-        self.client
+        self.http_client
             .request(
                 Method::POST,
                 format!("{}/accounts/register", self.urls.base),
@@ -194,13 +636,24 @@ impl AnonymousClient {
 
 /// A client used for interacting with the Bitwarden API.
 ///
+/// The client can be cloned cheaply and used from multiple tasks at the same time. Clones share
+/// the cache, the credentials, the access token and the lock state, and only one access token
+/// refresh is performed at a time.
+///
+/// A client can be locked with [`Client::lock`], which removes the source key from memory. A
+/// locked client can still send requests, but data can not be decrypted until the client is
+/// unlocked with [`Client::unlock`].
+///
 /// # Example
 ///
 /// Creating a [`Client`]:
 ///
 /// ```no_run
-/// use rwarden::{cache::EmptyCache, AccessTokenData, Client, Urls};
-/// use std::time::SystemTime;
+/// # fn main() -> Result<(), reqwest::Error> {
+/// use rwarden::{cache::EmptyCache, crypto::Kdf, AccessTokenData, Client, Credentials};
+/// use rwarden::{HttpConfig, RetryPolicy, Urls};
+/// use std::time::{Duration, SystemTime};
+/// use zeroize::Zeroizing;
 ///
 /// # let source_key: rwarden::crypto::SourceKey = unimplemented!();
 /// # let encrypted_symmetric_key: rwarden::crypto::SymmetricEncryptedBytes = unimplemented!();
@@ -208,41 +661,200 @@ impl AnonymousClient {
 /// let client = Client::builder()
 ///     .cache(EmptyCache)
 ///     .urls(Urls::official())
-///     .source_key(source_key)
+///     .email("foo@example.com")
+///     .kdf(Kdf::pbkdf2_sha256(100_000))
+///     .source_key(Some(source_key)) // optional, the client is locked if this is `None`
+///     .lock_timeout(Some(Duration::from_secs(600))) // optional
 ///     .encrypted_symmetric_key(encrypted_symmetric_key)
 ///     .encrypted_private_key(Some(encrypted_private_key)) // optional
-///     .refresh_token("foo")
+///     .credentials(Credentials::from("foo")) // refresh token or `ApiKey`
 ///     .access_token_data(Some(AccessTokenData { // optional
-///         access_token: "bar".to_owned(),
+///         access_token: Zeroizing::new("bar".to_owned()),
 ///         expiry_time: SystemTime::now(),
 ///     }))
+///     .http_client(HttpConfig::new().with_timeout(Duration::from_secs(30)).build()?) // optional
+///     .retry_policy(RetryPolicy::new().with_max_retries(5)) // optional
 ///     .build();
+/// # Ok(())
+/// # }
 /// ```
-#[derive(Debug, Clone, TypedBuilder)]
+#[derive(Debug, TypedBuilder)]
 pub struct Client<TCache> {
-    #[builder(default, setter(skip))]
-    client: reqwest::Client,
-    cache: TCache,
+    #[builder(default)]
+    http_client: reqwest::Client,
+    #[builder(
+        default,
+        setter(transform = |retry_policy: RetryPolicy| Arc::new(RwLock::new(retry_policy)))
+    )]
+    retry_policy: Arc<RwLock<RetryPolicy>>,
+    #[builder(setter(transform = |cache: TCache| Arc::new(Mutex::new(cache))))]
+    cache: Arc<Mutex<TCache>>,
     urls: Urls,
+    #[builder(setter(transform = |email: impl Into<String>| Arc::new(RwLock::new(email.into()))))]
+    email: Arc<RwLock<String>>,
+    #[builder(setter(transform = |kdf: Kdf| Arc::new(RwLock::new(kdf))))]
+    kdf: Arc<RwLock<Kdf>>,
+    #[builder(
+        default,
+        setter(transform = |source_key: Option<SourceKey>| Arc::new(RwLock::new(source_key)))
+    )]
+    source_key: Arc<RwLock<Option<SourceKey>>>,
+    #[builder(default = Arc::new(RwLock::new(Instant::now())), setter(skip))]
+    last_activity: Arc<RwLock<Instant>>,
+    #[builder(default, setter(skip))]
+    lock_timer: Arc<RwLock<LockTimer>>,
+    #[builder(
+        default,
+        setter(transform = |lock_timeout: Option<Duration>| Arc::new(RwLock::new(lock_timeout)))
+    )]
+    lock_timeout: Arc<RwLock<Option<Duration>>>,
+    #[builder(setter(transform = |encrypted_symmetric_key: SymmetricEncryptedBytes| {
+        Arc::new(RwLock::new(encrypted_symmetric_key))
+    }))]
+    encrypted_symmetric_key: Arc<RwLock<SymmetricEncryptedBytes>>,
+    #[builder(
+        default,
+        setter(transform = |encrypted_private_key: Option<SymmetricEncryptedBytes>| {
+            Arc::new(RwLock::new(encrypted_private_key))
+        })
+    )]
+    encrypted_private_key: Arc<RwLock<Option<SymmetricEncryptedBytes>>>,
+    #[builder(setter(transform = |credentials: Credentials| Arc::new(RwLock::new(credentials))))]
+    credentials: Arc<RwLock<Credentials>>,
+    #[builder(
+        default,
+        setter(transform = |access_token_data: Option<AccessTokenData>| {
+            Arc::new(RwLock::new(access_token_data))
+        })
+    )]
+    access_token_data: Arc<RwLock<Option<AccessTokenData>>>,
+    #[builder(
+        default,
+        setter(transform = |session_observer: Option<SessionObserver>| {
+            Arc::new(RwLock::new(session_observer))
+        })
+    )]
+    session_observer: Arc<RwLock<Option<SessionObserver>>>,
+    #[builder(default, setter(skip))]
+    refresh_lock: Arc<Mutex<()>>,
+}
+
+/// A cipher in the request for rotating the symmetric key.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct CipherWithId {
+    id: Uuid,
+    #[serde(flatten)]
+    inner: cipher::RequestModel,
+}
+
+/// A send in the request for rotating the symmetric key.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendWithId {
+    id: Uuid,
+    #[serde(flatten)]
+    inner: send::RequestModel,
+}
+
+/// The result of [`Client::prepare_key_update`].
+struct KeyUpdate {
+    email: String,
+    kdf: Kdf,
     source_key: SourceKey,
     encrypted_symmetric_key: SymmetricEncryptedBytes,
-    #[builder(default)]
-    encrypted_private_key: Option<SymmetricEncryptedBytes>,
-    #[builder(setter(into))]
-    refresh_token: String,
-    #[builder(default)]
-    access_token_data: Option<AccessTokenData>,
+    master_password_hash: MasterPasswordHash,
+    new_master_password_hash: MasterPasswordHash,
 }
 
-impl<TCache> Client<TCache> {
-    /// Returns a shared reference to the cache.
-    pub fn cache(&self) -> &TCache {
-        &self.cache
+/// The task that locks a [`Client`] when the lock timeout expires, shared between its clones.
+#[derive(Debug, Default)]
+struct LockTimer {
+    task: Option<JoinHandle<()>>,
+    /// Whether the task has locked the client since the session observer was last notified.
+    has_locked: bool,
+}
+
+/// Removes the source key from memory when the lock timeout expires.
+///
+/// The task ends when the client is locked, when no lock timeout is set or when all clones of the
+/// client were dropped.
+async fn run_lock_timer(
+    lock_timer: Arc<RwLock<LockTimer>>,
+    source_key: Weak<RwLock<Option<SourceKey>>>,
+    last_activity: Arc<RwLock<Instant>>,
+    lock_timeout: Arc<RwLock<Option<Duration>>>,
+) {
+    loop {
+        let deadline = {
+            let mut lock_timer = write(&lock_timer);
+            let source_key = match source_key.upgrade() {
+                Some(v) => v,
+                None => return,
+            };
+            let mut source_key = write(&source_key);
+            let lock_timeout = *read(&lock_timeout);
+            let deadline = match lock_timeout {
+                Some(v) if source_key.is_some() => *read(&last_activity) + v,
+                _ => {
+                    lock_timer.task = None;
+                    return;
+                }
+            };
+            if deadline <= Instant::now() {
+                *source_key = None;
+                lock_timer.task = None;
+                lock_timer.has_locked = true;
+                return;
+            }
+            deadline
+        };
+        tokio::time::sleep_until(deadline.into()).await;
     }
+}
+
+// If multiple locks are held at the same time, they are always acquired in the order `email`, `kdf`,
+// `lock_timer`, `source_key`, `last_activity`, `encrypted_symmetric_key`, `encrypted_private_key`
+// to prevent deadlocks. The locks of `retry_policy` and `lock_timeout` are never held while acquiring another
+// lock.
+//
+// A poisoned lock only means that another thread panicked while holding it. The values behind the
+// locks are always left in a consistent state, so the poisoning is ignored.
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<TCache> Clone for Client<TCache> {
+    fn clone(&self) -> Self {
+        Self {
+            http_client: self.http_client.clone(),
+            retry_policy: Arc::clone(&self.retry_policy),
+            cache: Arc::clone(&self.cache),
+            urls: self.urls.clone(),
+            email: Arc::clone(&self.email),
+            kdf: Arc::clone(&self.kdf),
+            source_key: Arc::clone(&self.source_key),
+            last_activity: Arc::clone(&self.last_activity),
+            lock_timer: Arc::clone(&self.lock_timer),
+            lock_timeout: Arc::clone(&self.lock_timeout),
+            encrypted_symmetric_key: Arc::clone(&self.encrypted_symmetric_key),
+            encrypted_private_key: Arc::clone(&self.encrypted_private_key),
+            credentials: Arc::clone(&self.credentials),
+            access_token_data: Arc::clone(&self.access_token_data),
+            session_observer: Arc::clone(&self.session_observer),
+            refresh_lock: Arc::clone(&self.refresh_lock),
+        }
+    }
+}
 
-    /// Returns a mutable reference to the cache.
-    pub fn cache_mut(&mut self) -> &mut TCache {
-        &mut self.cache
+impl<TCache> Client<TCache> {
+    /// Locks and returns the cache.
+    pub async fn cache(&self) -> MutexGuard<'_, TCache> {
+        self.cache.lock().await
     }
 
     /// Returns the URLs of the API endpoints.
@@ -250,96 +862,652 @@ impl<TCache> Client<TCache> {
         &self.urls
     }
 
+    /// Returns the HTTP client.
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    /// Returns the policy for retrying failed requests.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        read(&self.retry_policy).clone()
+    }
+
+    /// Sets the policy for retrying failed requests.
+    ///
+    /// The policy is shared with all clones of the client.
+    pub fn set_retry_policy(&self, retry_policy: RetryPolicy) {
+        *write(&self.retry_policy) = retry_policy;
+    }
+
+    /// Returns the email address of the user.
+    pub fn email(&self) -> String {
+        read(&self.email).clone()
+    }
+
+    /// Returns the KDF that is used for deriving the source key.
+    pub fn kdf(&self) -> Kdf {
+        *read(&self.kdf)
+    }
+
     /// Returns the source key.
-    pub fn source_key(&self) -> &SourceKey {
-        &self.source_key
+    ///
+    /// This is `None` if the client is locked.
+    pub fn source_key(&self) -> Option<SourceKey> {
+        let source_key = read(&self.source_key);
+        if self.has_idle_timeout_expired() {
+            return None;
+        }
+        source_key.clone()
+    }
+
+    /// Returns `true` if the client is locked.
+    ///
+    /// A client is locked if [`Client::lock`] was called or if the lock timeout has expired.
+    pub fn is_locked(&self) -> bool {
+        let source_key = read(&self.source_key);
+        source_key.is_none() || self.has_idle_timeout_expired()
+    }
+
+    /// Locks the client by removing the source key from memory.
+    ///
+    /// The credentials are kept, so requests can still be sent while the client is locked.
+    pub fn lock(&self) {
+        let source_key = write(&self.source_key).take();
+        if source_key.is_some() {
+            self.notify_session_observer();
+        }
+    }
+
+    /// Unlocks the client with the master password.
+    ///
+    /// The source key is derived from the master password and checked against the encrypted
+    /// symmetric key. No request is sent to the server. The source key is derived on tokio's
+    /// blocking thread pool.
+    pub async fn unlock<P: AsRef<[u8]>>(&self, master_password: P) -> Result<(), KeyError> {
+        let source_key = SourceKey::derive(self.email(), master_password, &self.kdf()).await;
+        let mut current_source_key = write(&self.source_key);
+        let mut last_activity = write(&self.last_activity);
+        SymmetricKey::new(&source_key, &read(&self.encrypted_symmetric_key))?;
+        *current_source_key = Some(source_key);
+        *last_activity = Instant::now();
+        drop((current_source_key, last_activity));
+        self.start_lock_timer(false);
+        self.notify_session_observer();
+        Ok(())
+    }
+
+    /// Returns the duration after which the client is locked automatically.
+    pub fn lock_timeout(&self) -> Option<Duration> {
+        *read(&self.lock_timeout)
+    }
+
+    /// Sets the duration after which the client is locked automatically.
+    ///
+    /// The timeout is reset when the client is unlocked and whenever a request is sent. The
+    /// timeout is shared with all clones of the client.
+    ///
+    /// When the timeout expires, the source key is removed from memory by a task on the tokio
+    /// runtime and the session observer is notified when the client is used the next time. If
+    /// the client is not used inside a tokio runtime, the source key is only removed when the
+    /// client is used the next time.
+    pub fn set_lock_timeout(&self, lock_timeout: Option<Duration>) {
+        *write(&self.lock_timeout) = lock_timeout;
+        self.start_lock_timer(true);
+    }
+
+    /// Starts the task that locks the client when the lock timeout expires.
+    ///
+    /// If `restart` is `true`, a running task is aborted first, otherwise nothing is done if the
+    /// task is already running. The task is only started if the client is unlocked and a lock
+    /// timeout is set.
+    fn start_lock_timer(&self, restart: bool) {
+        let mut lock_timer = write(&self.lock_timer);
+        if restart {
+            if let Some(task) = lock_timer.task.take() {
+                task.abort();
+            }
+        }
+        if lock_timer.task.is_some()
+            || read(&self.source_key).is_none()
+            || self.lock_timeout().is_none()
+        {
+            return;
+        }
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            lock_timer.task = Some(runtime.spawn(run_lock_timer(
+                Arc::clone(&self.lock_timer),
+                Arc::downgrade(&self.source_key),
+                Arc::clone(&self.last_activity),
+                Arc::clone(&self.lock_timeout),
+            )));
+        }
+    }
+
+    /// Returns `true` if the lock timeout has expired.
+    ///
+    /// The lock of the source key must be held by the caller, so that the client is not unlocked
+    /// in the meantime.
+    fn has_idle_timeout_expired(&self) -> bool {
+        match self.lock_timeout() {
+            Some(v) => read(&self.last_activity).elapsed() >= v,
+            None => false,
+        }
+    }
+
+    /// Locks the client if the lock timeout has expired and resets the timeout.
+    fn record_activity(&self) {
+        let has_locked_by_timer = mem::take(&mut write(&self.lock_timer).has_locked);
+        let mut source_key = write(&self.source_key);
+        let has_locked = self.has_idle_timeout_expired() && source_key.take().is_some();
+        *write(&self.last_activity) = Instant::now();
+        drop(source_key);
+        self.start_lock_timer(false);
+        if has_locked || has_locked_by_timer {
+            self.notify_session_observer();
+        }
     }
 
     /// Returns the encrypted symmetric key.
-    pub fn encrypted_symmetric_key(&self) -> &SymmetricEncryptedBytes {
-        &self.encrypted_symmetric_key
+    pub fn encrypted_symmetric_key(&self) -> SymmetricEncryptedBytes {
+        read(&self.encrypted_symmetric_key).clone()
     }
 
     /// Decrypts and returns the symmetric key.
-    pub fn symmetric_key(&self) -> Result<SymmetricKey, crypto::SymmetricKeyError> {
-        SymmetricKey::new(&self.source_key, &self.encrypted_symmetric_key)
+    pub fn symmetric_key(&self) -> Result<SymmetricKey, KeyError> {
+        let source_key = read(&self.source_key);
+        let source_key = match &*source_key {
+            Some(v) if !self.has_idle_timeout_expired() => v,
+            _ => return Err(KeyError::Locked),
+        };
+        Ok(SymmetricKey::new(
+            source_key,
+            &read(&self.encrypted_symmetric_key),
+        )?)
     }
 
     /// Returns the encrypted private key.
-    pub fn encrypted_private_key(&self) -> Option<&SymmetricEncryptedBytes> {
-        self.encrypted_private_key.as_ref()
+    ///
+    /// This is `None` if the account has no key pair (see [`Client::generate_key_pair`]).
+    pub fn encrypted_private_key(&self) -> Option<SymmetricEncryptedBytes> {
+        read(&self.encrypted_private_key).clone()
     }
 
     /// Decrypts and returns the private key.
     pub fn private_key(&self) -> Result<RsaPrivateKey, PrivateKeyError> {
         let symmetric_key = self.symmetric_key()?;
-        let private_key = match &self.encrypted_private_key {
+        let mut private_key = match &*read(&self.encrypted_private_key) {
             Some(v) => v.decrypt(&symmetric_key)?,
             None => return Err(PrivateKeyError::NotAvailable),
         };
-        Ok(RsaPrivateKey::from_pkcs8_der(&private_key)?)
+        let result = RsaPrivateKey::from_pkcs8_der(&private_key);
+        private_key.zeroize();
+        Ok(result?)
+    }
+
+    /// Creates a [`KeyRing`] with the key of the user and the keys of the given organizations.
+    ///
+    /// The organizations are usually taken from [`Account::organizations`]. The private key is
+    /// only needed if there are organizations.
+    ///
+    /// [`Account::organizations`]: account::Account::organizations
+    pub fn key_ring(
+        &self,
+        organizations: &[organization::AccountOrganization],
+    ) -> Result<KeyRing, KeyRingError> {
+        let mut key_ring = KeyRing::new(self.symmetric_key()?);
+        if !organizations.is_empty() {
+            key_ring.insert_organizations(&self.private_key()?, organizations)?;
+        }
+        Ok(key_ring)
+    }
+
+    /// Returns the credentials that are used for retrieving new access tokens.
+    pub fn credentials(&self) -> Credentials {
+        read(&self.credentials).clone()
     }
 
     /// Returns the refresh token.
-    pub fn refresh_token(&self) -> &str {
-        &self.refresh_token
+    ///
+    /// This is `None` if the client uses an API key for retrieving new access tokens.
+    pub fn refresh_token(&self) -> Option<String> {
+        match &*read(&self.credentials) {
+            Credentials::RefreshToken(v) => Some(v.to_string()),
+            Credentials::ApiKey(_) => None,
+        }
     }
 
     /// Returns the access token and its expiry time.
-    pub fn access_token_data(&self) -> Option<&AccessTokenData> {
-        self.access_token_data.as_ref()
+    pub fn access_token_data(&self) -> Option<AccessTokenData> {
+        read(&self.access_token_data).clone()
+    }
+
+    /// Returns a snapshot of the current session.
+    ///
+    /// The session can be used for creating a new client without logging in again (see
+    /// [`Session::into_client`]).
+    pub fn session(&self) -> Session {
+        let email = read(&self.email);
+        let kdf = read(&self.kdf);
+        let source_key = read(&self.source_key);
+        let source_key = if self.has_idle_timeout_expired() {
+            None
+        } else {
+            source_key.clone()
+        };
+        Session {
+            urls: self.urls.clone(),
+            email: email.clone(),
+            kdf: *kdf,
+            source_key,
+            encrypted_symmetric_key: read(&self.encrypted_symmetric_key).clone(),
+            encrypted_private_key: read(&self.encrypted_private_key).clone(),
+            credentials: self.credentials(),
+            access_token_data: self.access_token_data(),
+        }
+    }
+
+    /// Sets a function that is called with the new session whenever the session changes (e.g.
+    /// when the access token is refreshed).
+    pub fn set_session_observer<F>(&self, f: F)
+    where
+        F: Fn(&Session) + Send + Sync + 'static,
+    {
+        *write(&self.session_observer) = Some(SessionObserver::new(f));
+    }
+
+    /// Removes the function that was set with [`Client::set_session_observer`].
+    pub fn remove_session_observer(&self) {
+        *write(&self.session_observer) = None;
+    }
+
+    /// Uploads the encrypted data of an attachment or a file send.
+    ///
+    /// Direct uploads are sent to the server, Azure uploads are sent to the given URL.
+    pub(crate) async fn upload_file(
+        &self,
+        file_upload_type: FileUploadType,
+        direct_url: String,
+        azure_url: &str,
+        file_name: String,
+        body: reqwest::Body,
+        length: u64,
+    ) -> Result<(), RequestResponseError> {
+        match file_upload_type {
+            FileUploadType::Direct => {
+                let part = multipart::Part::stream_with_length(body, length).file_name(file_name);
+                self.request(Method::POST, direct_url)
+                    .await?
+                    .multipart(multipart::Form::new().part("data", part))
+                    .send()
+                    .await?
+                    .parse_empty()
+                    .await?;
+            }
+            FileUploadType::Azure => {
+                self.http_client()
+                    .put(azure_url)
+                    .header("x-ms-blob-type", "BlockBlob")
+                    .header("x-ms-version", "2020-04-08")
+                    .header(header::CONTENT_LENGTH, length)
+                    .body(body)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+        }
+        Ok(())
     }
 
     pub(crate) async fn request<S>(
-        &mut self,
+        &self,
         method: Method,
         url: S,
-    ) -> Result<RequestBuilder, RequestResponseError>
+    ) -> Result<RequestBuilder<'_, TCache>, RequestResponseError>
     where
         S: IntoUrl,
     {
-        let refresh_access_token = match &self.access_token_data {
-            Some(v) if v.token_has_expired() => true,
+        self.record_activity();
+        let refresh_access_token = match &*read(&self.access_token_data) {
+            Some(v) => v.token_has_expired(),
             None => true,
-            Some(_) => false,
         };
         if refresh_access_token {
-            self.refresh_access_token().await?;
+            self.refresh_access_token(None).await?;
         }
-        // `unwrap` is safe here because the `refresh_access_token` function sets the access token
-        let access_token = &self.access_token_data.as_ref().unwrap().access_token;
-        Ok(self
-            .client
-            .request(method, url)
-            .header(header::AUTHORIZATION, format!("Bearer {}", access_token)))
+        let idempotent = matches!(
+            method,
+            Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+        );
+        let inner = self.http_client.request(method, url);
+        Ok(RequestBuilder {
+            client: self,
+            inner,
+            idempotent,
+        })
+    }
+
+    fn access_token(&self) -> String {
+        // `unwrap` is safe here because the access token is always set before a request is sent
+        read(&self.access_token_data)
+            .as_ref()
+            .unwrap()
+            .access_token
+            .to_string()
     }
 
     /// Refreshes the access token.
-    async fn refresh_access_token(&mut self) -> Result<(), RequestResponseError> {
-        let token = self
-            .client
-            .request(Method::POST, self.urls.auth.clone())
-            .form(&[
+    ///
+    /// If `rejected_access_token` is set, the access token is only refreshed if it is still the
+    /// current one. Otherwise it is only refreshed if it has expired. This makes sure that the
+    /// access token is refreshed only once if multiple requests need a new access token at the
+    /// same time.
+    async fn refresh_access_token(
+        &self,
+        rejected_access_token: Option<&str>,
+    ) -> Result<(), RequestResponseError> {
+        let _refresh_guard = self.refresh_lock.lock().await;
+        let is_refreshed = match (&*read(&self.access_token_data), rejected_access_token) {
+            (Some(v), Some(rejected)) => v.access_token.as_str() != rejected,
+            (Some(v), None) => !v.token_has_expired(),
+            (None, _) => false,
+        };
+        if is_refreshed {
+            return Ok(());
+        }
+        let credentials = self.credentials();
+        let request = self
+            .http_client
+            .request(Method::POST, self.urls.auth.clone());
+        let request = match &credentials {
+            Credentials::RefreshToken(refresh_token) => request.form(&[
                 ("grant_type", "refresh_token"),
-                ("refresh_token", &self.refresh_token),
-            ])
+                ("refresh_token", refresh_token.as_str()),
+            ]),
+            Credentials::ApiKey(api_key) => request.form(&api_key_form(api_key)),
+        };
+        let token = request
             .send()
             .await?
-            .parse::<TokenResponse>()
+            .parse::<RefreshTokenResponse>()
             .await?;
-        self.refresh_token = token.refresh_token;
-        self.access_token_data = Some(AccessTokenData {
-            access_token: token.access_token,
+        if let (Credentials::RefreshToken(_), Some(v)) = (&credentials, token.refresh_token) {
+            *write(&self.credentials) = Credentials::from(v);
+        }
+        *write(&self.access_token_data) = Some(AccessTokenData {
+            access_token: Zeroizing::new(token.access_token),
             expiry_time: SystemTime::now() + Duration::from_secs(token.expires_in),
         });
+        self.notify_session_observer();
+        Ok(())
+    }
+
+    fn notify_session_observer(&self) {
+        let observer = read(&self.session_observer).clone();
+        if let Some(observer) = observer {
+            observer.notify(&self.session());
+        }
+    }
+
+    /// Changes the master password.
+    ///
+    /// The symmetric key is not changed, it is only encrypted with the source key that is derived
+    /// from the new master password. The client is updated to use the new source key, so it stays
+    /// unlocked. Depending on the server, the refresh tokens of all sessions may be revoked, in
+    /// which case a new login is required.
+    pub async fn change_master_password<P, N, H>(
+        &self,
+        master_password: P,
+        new_master_password: N,
+        master_password_hint: Option<H>,
+    ) -> Result<(), AccountKeyError>
+    where
+        P: AsRef<[u8]>,
+        N: AsRef<[u8]>,
+        H: Into<String>,
+    {
+        let update = self
+            .prepare_key_update(
+                master_password.as_ref(),
+                None,
+                Some(new_master_password.as_ref()),
+                None,
+            )
+            .await?;
+        let request = account::ModifyPassword {
+            master_password_hash: update.master_password_hash.clone(),
+            new_master_password_hash: update.new_master_password_hash.clone(),
+            master_password_hint: master_password_hint.map(Into::into),
+            key: update.encrypted_symmetric_key.clone(),
+        };
+        request.execute(self).await?;
+        self.apply_key_update(update);
+        Ok(())
+    }
+
+    /// Changes the KDF that is used for deriving the source key.
+    ///
+    /// The client is updated to use the new source key, so it stays unlocked. Depending on the
+    /// server, the refresh tokens of all sessions may be revoked, in which case a new login is
+    /// required.
+    pub async fn change_kdf<P>(&self, master_password: P, kdf: Kdf) -> Result<(), AccountKeyError>
+    where
+        P: AsRef<[u8]>,
+    {
+        let update = self
+            .prepare_key_update(master_password.as_ref(), None, None, Some(kdf))
+            .await?;
+        let request = account::ModifyKdf {
+            kdf,
+            master_password_hash: update.master_password_hash.clone(),
+            new_master_password_hash: update.new_master_password_hash.clone(),
+            key: update.encrypted_symmetric_key.clone(),
+        };
+        request.execute(self).await?;
+        self.apply_key_update(update);
+        Ok(())
+    }
+
+    /// Changes the email address.
+    ///
+    /// The token can be requested with [`Client::send_email_modification_token`]. Since the email
+    /// address is used as the salt for deriving the source key, the symmetric key is encrypted
+    /// with a new source key. The client is updated to use the new source key, so it stays
+    /// unlocked. Depending on the server, the refresh tokens of all sessions may be revoked, in
+    /// which case a new login is required.
+    pub async fn change_email<P, E, T>(
+        &self,
+        master_password: P,
+        new_email: E,
+        token: T,
+    ) -> Result<(), AccountKeyError>
+    where
+        P: AsRef<[u8]>,
+        E: Into<String>,
+        T: Into<String>,
+    {
+        let new_email = new_email.into();
+        let update = self
+            .prepare_key_update(master_password.as_ref(), Some(new_email), None, None)
+            .await?;
+        let request = account::ModifyEmail {
+            new_email: update.email.clone(),
+            master_password_hash: update.master_password_hash.clone(),
+            new_master_password_hash: update.new_master_password_hash.clone(),
+            token: token.into(),
+            key: update.encrypted_symmetric_key.clone(),
+        };
+        request.execute(self).await?;
+        self.apply_key_update(update);
         Ok(())
     }
 
+    /// Rotates the symmetric key of the account.
+    ///
+    /// A new symmetric key is generated and all personal ciphers (including the password history
+    /// and the keys of the attachments), all folders, the key material of all sends and the
+    /// private key are encrypted with it.
+    /// Ciphers that are owned by an organization are not affected. The client and the cache are
+    /// updated afterwards. Depending on the server, the refresh tokens of all sessions may be
+    /// revoked, in which case a new login is required.
+    pub async fn rotate_symmetric_key<P>(
+        &self,
+        master_password: P,
+    ) -> Result<(), KeyRotationError<TCache::Error>>
+    where
+        TCache: Cache + Send,
+        P: AsRef<[u8]>,
+    {
+        let master_password = master_password.as_ref();
+        let kdf = self.kdf();
+        let source_key = SourceKey::derive(self.email(), master_password, &kdf).await;
+        let old_key = SymmetricKey::new(&source_key, &self.encrypted_symmetric_key())
+            .map_err(|_| KeyRotationError::InvalidMasterPassword)?;
+        let master_password_hash =
+            MasterPasswordHash::derive(&source_key, master_password, kdf.kdf_type()).await;
+
+        let mut sync = self.send(&sync::Get).await?;
+        for cipher in &sync.ciphers {
+            if cipher.inner.organization_id.is_some() {
+                continue;
+            }
+            if let Some(attachment) = cipher.inner.attachments.iter().find(|v| v.key.is_none()) {
+                return Err(KeyRotationError::LegacyAttachment {
+                    cipher_id: cipher.inner.id,
+                    attachment_id: attachment.id,
+                });
+            }
+            if let cipher::Type::Unknown { .. } = cipher.inner.ty {
+                return Err(KeyRotationError::UnknownCipherType {
+                    cipher_id: cipher.inner.id,
+                });
+            }
+        }
+        let new_key = SymmetricKey::generate();
+        let encrypted_symmetric_key = new_key.protect(&source_key).into_symmetric();
+        let mut encrypted_private_key = sync.account.private_key.clone();
+        if let Some(v) = &mut encrypted_private_key {
+            util::reencrypt(v, &old_key, &new_key)?;
+        }
+        let mut ciphers = Vec::new();
+        for cipher in &mut sync.ciphers {
+            if cipher.inner.organization_id.is_some() {
+                continue;
+            }
+            cipher.inner.reencrypt(&old_key, &new_key)?;
+            ciphers.push(CipherWithId {
+                id: cipher.inner.id,
+                inner: cipher.inner.clone().into(),
+            });
+        }
+        let mut folders = Vec::new();
+        for folder in &mut sync.folders {
+            folder.reencrypt(&old_key, &new_key)?;
+            folders.push(json!({ "Id": folder.id, "Name": folder.name }));
+        }
+        let mut sends = Vec::new();
+        for send in &mut sync.sends {
+            send.reencrypt(&old_key, &new_key)?;
+            sends.push(SendWithId {
+                id: send.id,
+                inner: send.clone().into(),
+            });
+        }
+
+        self.request(Method::POST, format!("{}/accounts/key", self.urls().base))
+            .await?
+            .json(&json!({
+                "MasterPasswordHash": master_password_hash,
+                "Key": encrypted_symmetric_key,
+                "PrivateKey": encrypted_private_key,
+                "Ciphers": ciphers,
+                "Folders": folders,
+                "Sends": sends,
+            }))
+            .send()
+            .await?
+            .parse_empty()
+            .await?;
+
+        {
+            let mut symmetric_key = write(&self.encrypted_symmetric_key);
+            let mut private_key = write(&self.encrypted_private_key);
+            *symmetric_key = encrypted_symmetric_key.clone();
+            *private_key = encrypted_private_key.clone();
+        }
+        self.notify_session_observer();
+
+        sync.account.key = encrypted_symmetric_key;
+        sync.account.private_key = encrypted_private_key;
+        self.cache()
+            .await
+            .sync(&sync)
+            .await
+            .map_err(KeyRotationError::Cache)?;
+        Ok(())
+    }
+
+    /// Derives the source keys and master password hashes for changing the email address, the
+    /// master password or the KDF, and encrypts the symmetric key with the new source key.
+    async fn prepare_key_update(
+        &self,
+        master_password: &[u8],
+        new_email: Option<String>,
+        new_master_password: Option<&[u8]>,
+        new_kdf: Option<Kdf>,
+    ) -> Result<KeyUpdate, AccountKeyError> {
+        let email = self.email();
+        let kdf = self.kdf();
+        let source_key = SourceKey::derive(&email, master_password, &kdf).await;
+        let symmetric_key = SymmetricKey::new(&source_key, &self.encrypted_symmetric_key())
+            .map_err(|_| AccountKeyError::InvalidMasterPassword)?;
+        let master_password_hash =
+            MasterPasswordHash::derive(&source_key, master_password, kdf.kdf_type()).await;
+
+        let email = new_email.unwrap_or(email);
+        let new_master_password = new_master_password.unwrap_or(master_password);
+        let kdf = new_kdf.unwrap_or(kdf);
+        let source_key = SourceKey::derive(&email, new_master_password, &kdf).await;
+        let new_master_password_hash =
+            MasterPasswordHash::derive(&source_key, new_master_password, kdf.kdf_type()).await;
+        let encrypted_symmetric_key = symmetric_key.protect(&source_key).into_symmetric();
+        Ok(KeyUpdate {
+            email,
+            kdf,
+            source_key,
+            encrypted_symmetric_key,
+            master_password_hash,
+            new_master_password_hash,
+        })
+    }
+
+    fn apply_key_update(&self, update: KeyUpdate) {
+        let mut email = write(&self.email);
+        let mut kdf = write(&self.kdf);
+        let mut source_key = write(&self.source_key);
+        let mut last_activity = write(&self.last_activity);
+        let mut encrypted_symmetric_key = write(&self.encrypted_symmetric_key);
+        *email = update.email;
+        *kdf = update.kdf;
+        *source_key = Some(update.source_key);
+        *last_activity = Instant::now();
+        *encrypted_symmetric_key = update.encrypted_symmetric_key;
+        drop((
+            email,
+            kdf,
+            source_key,
+            last_activity,
+            encrypted_symmetric_key,
+        ));
+        self.start_lock_timer(false);
+        self.notify_session_observer();
+    }
+
     /// Sends a token to the given email address that can be used to change the email address.
     ///
-    /// To change the email address with the token, [`account::ModifyEmail`] can be used.
+    /// To change the email address with the token, [`Client::change_email`] or
+    /// [`account::ModifyEmail`] can be used.
     pub async fn send_email_modification_token<S: AsRef<str>>(
-        &mut self,
+        &self,
         new_email: S,
         master_password_hash: &MasterPasswordHash,
     ) -> Result<(), RequestResponseError> {
@@ -363,7 +1531,7 @@ impl<TCache> Client<TCache> {
     ///
     /// To verify the email address with the token, the [`Client::verify_email`] function can be
     /// used.
-    pub async fn send_email_verification_token(&mut self) -> Result<(), RequestResponseError> {
+    pub async fn send_email_verification_token(&self) -> Result<(), RequestResponseError> {
         self.request(
             Method::POST,
             format!("{}/accounts/verify-email", self.urls().base),
@@ -376,13 +1544,13 @@ impl<TCache> Client<TCache> {
         Ok(())
     }
 
-    pub async fn verify_email<S>(&mut self, token: S) -> crate::Result<(), TCache::Error>
+    pub async fn verify_email<S>(&self, token: S) -> crate::Result<(), TCache::Error>
     where
         TCache: Cache + Send,
         S: AsRef<str>,
     {
         let account = self.send(&account::Get).await?;
-        self.client
+        self.http_client
             .request(
                 Method::POST,
                 format!("{}/accounts/verify-email-token", self.urls().base),
@@ -396,7 +1564,7 @@ impl<TCache> Client<TCache> {
     }
 
     pub async fn verify_password(
-        &mut self,
+        &self,
         master_password_hash: &MasterPasswordHash,
     ) -> Result<(), RequestResponseError> {
         self.request(
@@ -412,10 +1580,557 @@ impl<TCache> Client<TCache> {
         Ok(())
     }
 
-    pub fn send<'request, 'client, R>(&'client mut self, request: &'request R) -> R::Output
+    /// Generates a new RSA key pair and uploads it to the server.
+    ///
+    /// This is only needed for accounts that do not have a key pair yet (e.g. accounts that were
+    /// registered with an older version of this crate). Without a key pair, the account can not
+    /// join organizations or receive shared items.
+    pub async fn generate_key_pair(&self) -> Result<(), KeyPairError> {
+        if read(&self.encrypted_private_key).is_some() {
+            return Err(KeyPairError::AlreadyExists);
+        }
+        let symmetric_key = self.symmetric_key()?;
+        let key_pair = RsaKeyPair::generate_async().await;
+        let request = account::SetKeys {
+            public_key: key_pair.encode_public_key(),
+            encrypted_private_key: key_pair.protect(&symmetric_key),
+        };
+        request.execute(self).await?;
+        *write(&self.encrypted_private_key) = Some(request.encrypted_private_key);
+        self.notify_session_observer();
+        Ok(())
+    }
+
+    /// Creates a new organization with the free plan.
+    ///
+    /// A new symmetric key is generated for the organization and encrypted with the public key of
+    /// the user. The organization also gets a new key pair and a default collection with the given
+    /// name. The organization is part of [`Account::organizations`] after the next
+    /// synchronization.
+    ///
+    /// [`Account::organizations`]: account::Account::organizations
+    pub async fn create_organization<N, E, C>(
+        &self,
+        name: N,
+        billing_email: E,
+        collection_name: C,
+    ) -> Result<organization::Organization, OrganizationKeyError>
+    where
+        N: AsRef<str>,
+        E: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let public_key = self.private_key()?.to_public_key();
+        let organization_key = SymmetricKey::generate();
+        // `unwrap` is safe here because generated symmetric keys always have a MAC key
+        let mut key = [organization_key.enc, organization_key.mac.unwrap()].concat();
+        let encrypted_key =
+            GenericEncryptedBytes::<Rsa2048OaepSha1>::encrypt(&key, &public_key).into_asymmetric();
+        key.zeroize();
+        let key_pair = RsaKeyPair::generate_async().await;
+        let collection_name =
+            SymmetricEncryptedString::encrypt(collection_name.as_ref(), &organization_key);
+        let request = organization::Create {
+            name: name.as_ref().to_owned(),
+            billing_email: billing_email.as_ref().to_owned(),
+            plan_type: organization::PlanType::Free,
+            key: encrypted_key,
+            collection_name,
+            keys: organization::KeysRequestModel {
+                public_key: key_pair.encode_public_key(),
+                encrypted_private_key: key_pair.protect(&organization_key),
+            },
+        };
+        Ok(request.execute(self).await?)
+    }
+
+    /// Confirms a user that accepted the invitation to an organization.
+    ///
+    /// The symmetric key of the organization is encrypted with the public key of the user.
+    pub async fn confirm_organization_user(
+        &self,
+        organization_id: Uuid,
+        user: &organization::User,
+    ) -> Result<(), OrganizationKeyError> {
+        let mut key = self.organization_key(organization_id).await?;
+        let encrypted_key = self.encrypt_organization_key(&key, user).await;
+        key.zeroize();
+        let request = organization::ConfirmUser {
+            organization_id,
+            user_id: user.id,
+            key: encrypted_key?,
+        };
+        request.execute(self).await?;
+        Ok(())
+    }
+
+    /// Confirms multiple users that accepted the invitation to an organization.
+    ///
+    /// Returns the response of the server for each user that was sent to it. Users whose public
+    /// key can not be retrieved (e.g. because they have not accepted the invitation yet) are not
+    /// sent to the server, they are part of [`ConfirmUsersResponse::failures`] instead.
+    ///
+    /// [`ConfirmUsersResponse::failures`]: organization::ConfirmUsersResponse::failures
+    pub async fn confirm_organization_users<'a, I>(
+        &self,
+        organization_id: Uuid,
+        users: I,
+    ) -> Result<organization::ConfirmUsersResponse, OrganizationKeyError>
+    where
+        I: IntoIterator<Item = &'a organization::User>,
+    {
+        let mut key = self.organization_key(organization_id).await?;
+        let mut keys = Vec::new();
+        let mut failures = Vec::new();
+        for user in users {
+            match self.encrypt_organization_key(&key, user).await {
+                Ok(v) => keys.push(organization::ConfirmUserKey {
+                    id: user.id,
+                    key: v,
+                }),
+                Err(e) => failures.push(organization::ConfirmUserFailure {
+                    id: user.id,
+                    error: e,
+                }),
+            }
+        }
+        key.zeroize();
+        let responses = if keys.is_empty() {
+            Vec::new()
+        } else {
+            let request = organization::ConfirmUsers {
+                organization_id,
+                keys,
+            };
+            request.execute(self).await?
+        };
+        Ok(organization::ConfirmUsersResponse {
+            responses,
+            failures,
+        })
+    }
+
+    /// Retrieves and decrypts the symmetric key of an organization.
+    ///
+    /// The profile is requested from the server, so that organizations that were joined after the
+    /// last sync are also available.
+    async fn organization_key(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<u8>, OrganizationKeyError> {
+        let account: account::Account = self
+            .request(
+                Method::GET,
+                format!("{}/accounts/profile", self.urls().base),
+            )
+            .await?
+            .send()
+            .await?
+            .parse()
+            .await?;
+        let organization = account
+            .organizations
+            .into_iter()
+            .find(|v| v.id == organization_id)
+            .ok_or(OrganizationKeyError::OrganizationNotFound)?;
+        let private_key = self.private_key()?;
+        Ok(organization.key.decrypt(&private_key)?)
+    }
+
+    /// Encrypts the symmetric key of an organization with the public key of a user.
+    async fn encrypt_organization_key(
+        &self,
+        key: &[u8],
+        user: &organization::User,
+    ) -> Result<AsymmetricEncryptedBytes, OrganizationKeyError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Response {
+            public_key: String,
+        }
+
+        let user_id = user.user_id.ok_or(OrganizationKeyError::NotAccepted)?;
+        let response: Response = self
+            .request(
+                Method::GET,
+                format!("{}/users/{}/public-key", self.urls().base, user_id),
+            )
+            .await?
+            .send()
+            .await?
+            .parse()
+            .await?;
+        let public_key = base64::decode(&response.public_key)
+            .ok()
+            .and_then(|v| RsaPublicKey::from_public_key_der(&v).ok())
+            // Keys with less than 2048 bits are too short for encrypting the organization key
+            .filter(|v| v.size() >= 256)
+            .ok_or(OrganizationKeyError::InvalidPublicKey)?;
+        Ok(GenericEncryptedBytes::<Rsa2048OaepSha1>::encrypt(key, &public_key).into_asymmetric())
+    }
+
+    /// Encrypts a file and adds it as an attachment to a cipher.
+    ///
+    /// A new key is generated for the attachment, the key and the file name are encrypted with the
+    /// key of the owner of the cipher.
+    pub async fn create_attachment<N>(
+        &self,
+        cipher: &cipher::Cipher,
+        file_name: N,
+        data: &[u8],
+        key_ring: &KeyRing,
+    ) -> Result<cipher::Cipher, AttachmentError>
+    where
+        N: AsRef<str>,
+    {
+        let key = attachment_owner_key(cipher, key_ring)?;
+        let file_name = SymmetricEncryptedString::encrypt(file_name.as_ref(), key);
+        Ok(self
+            .create_encrypted_attachment(cipher.id, file_name, data, key)
+            .await?)
+    }
+
+    /// Generates a new attachment key, encrypts the data with it and creates the attachment.
+    async fn create_encrypted_attachment(
+        &self,
+        cipher_id: Uuid,
+        file_name: SymmetricEncryptedString,
+        data: &[u8],
+        key: &SymmetricKey,
+    ) -> Result<cipher::Cipher, RequestResponseError> {
+        let (attachment_key, encrypted_key) = generate_attachment_key(key);
+        cipher::CreateAttachment {
+            cipher_id,
+            file_name,
+            key: encrypted_key,
+            data: SymmetricEncryptedBytes::encrypt(data, &attachment_key)
+                .0
+                .to_bytes(),
+        }
+        .execute(self)
+        .await
+    }
+
+    /// Downloads and decrypts the data of an attachment.
+    ///
+    /// The download URL of the attachment expires after some time, a new one can be retrieved
+    /// with [`cipher::GetAttachment`].
+    pub async fn download_attachment(
+        &self,
+        cipher: &cipher::Cipher,
+        attachment: &cipher::Attachment,
+        key_ring: &KeyRing,
+    ) -> Result<Vec<u8>, AttachmentError> {
+        let key = attachment_owner_key(cipher, key_ring)?;
+        let data = self
+            .http_client()
+            .get(&attachment.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(attachment.decrypt_data(key, &data)?)
+    }
+
+    /// Encrypts the data of a reader and adds it as an attachment to a cipher.
+    ///
+    /// Unlike [`Client::create_attachment`], the data is not loaded into memory. It is encrypted
+    /// into the buffer first (e.g. a temporary file), because the size of the encrypted data must
+    /// be known before the upload, and the buffer is streamed to the server afterwards.
+    pub async fn create_attachment_from_reader<N, R, B>(
+        &self,
+        cipher: &cipher::Cipher,
+        file_name: N,
+        reader: &mut R,
+        buffer: B,
+        key_ring: &KeyRing,
+    ) -> Result<cipher::Cipher, AttachmentError>
+    where
+        N: AsRef<str>,
+        R: AsyncRead + Unpin,
+        B: AsyncRead + AsyncWrite + AsyncSeek + Unpin + Send + Sync + 'static,
+    {
+        let key = attachment_owner_key(cipher, key_ring)?;
+        let file_name = SymmetricEncryptedString::encrypt(file_name.as_ref(), key);
+        let (attachment_key, encrypted_key) = generate_attachment_key(key);
+        let (body, length) = encrypt_to_body(reader, buffer, &attachment_key).await?;
+        Ok(
+            cipher::create_attachment(self, cipher.id, &file_name, &encrypted_key, body, length)
+                .await?,
+        )
+    }
+
+    /// Downloads the data of an attachment and writes the decrypted data to the writer.
+    ///
+    /// Unlike [`Client::download_attachment`], the data is not loaded into memory. The encrypted
+    /// data is written to the buffer (e.g. a temporary file) first, so that the MAC can be
+    /// verified before any data is written to the writer. The buffer must not contain any data
+    /// after its current position. Returns the number of bytes written to the writer.
+    pub async fn download_attachment_to_writer<B, W>(
+        &self,
+        cipher: &cipher::Cipher,
+        attachment: &cipher::Attachment,
+        key_ring: &KeyRing,
+        buffer: &mut B,
+        writer: &mut W,
+    ) -> Result<u64, AttachmentError>
+    where
+        B: AsyncRead + AsyncWrite + AsyncSeek + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let key = attachment_owner_key(cipher, key_ring)?;
+        let attachment_key = attachment.decrypt_key(key)?;
+        let mac = attachment_key
+            .mac
+            .ok_or(AttachmentDecryptionError::Decryption(
+                symmetric_encryption::DecryptionError::MacKeyMissing,
+            ))?;
+        let start = buffer.seek(SeekFrom::Current(0)).await?;
+        let mut response = self
+            .http_client()
+            .get(&attachment.url)
+            .send()
+            .await?
+            .error_for_status()?;
+        while let Some(chunk) = response.chunk().await? {
+            buffer.write_all(&chunk).await?;
+        }
+        buffer.flush().await?;
+        buffer.seek(SeekFrom::Start(start)).await?;
+        let params = (attachment_key.enc, mac);
+        Ok(AesCbc256HmacSha256::decrypt_stream(buffer, writer, &params).await?)
+    }
+
+    /// Encrypts the data of a reader and creates a file send.
+    ///
+    /// The data is encrypted with the key that is derived from the key material of the send. Like
+    /// with [`Client::create_attachment_from_reader`], the data is encrypted into the buffer
+    /// first and the buffer is streamed to the server afterwards.
+    pub async fn create_file_send_from_reader<R, B>(
+        &self,
+        request_model: &send::RequestModel,
+        send_key: &SendKey,
+        reader: &mut R,
+        buffer: B,
+    ) -> Result<send::Send, FileUploadError>
+    where
+        R: AsyncRead + Unpin,
+        B: AsyncRead + AsyncWrite + AsyncSeek + Unpin + Send + Sync + 'static,
+    {
+        let (body, length) = encrypt_to_body(reader, buffer, &send_key.stretch()).await?;
+        Ok(send::create_file(self, request_model, body, length).await?)
+    }
+
+    /// Re-encrypts an attachment with a new key.
+    ///
+    /// Attachments that were created by old clients have no key and their data is encrypted with
+    /// the key of the owner of the cipher directly. Such attachments must be re-keyed before the
+    /// symmetric key of the account can be rotated. The attachment is uploaded again and the old
+    /// attachment is deleted afterwards, so the ID of the attachment changes.
+    pub async fn rekey_attachment(
+        &self,
+        cipher: &cipher::Cipher,
+        attachment: &cipher::Attachment,
+        key_ring: &KeyRing,
+    ) -> Result<cipher::Cipher, AttachmentError> {
+        let key = attachment_owner_key(cipher, key_ring)?;
+        let mut data = self
+            .download_attachment(cipher, attachment, key_ring)
+            .await?;
+        // The file name is encrypted with the key of the owner, so it can be reused
+        let result = self
+            .create_encrypted_attachment(cipher.id, attachment.file_name.clone(), &data, key)
+            .await;
+        data.zeroize();
+        let mut cipher = result?;
+        cipher::DeleteAttachment {
+            cipher_id: cipher.id,
+            attachment_id: attachment.id,
+        }
+        .execute(self)
+        .await?;
+        cipher.attachments.retain(|v| v.id != attachment.id);
+        Ok(cipher)
+    }
+
+    pub fn send<'request, 'client, R>(&'client self, request: &'request R) -> R::Output
     where
         R: Request<'request, 'client, TCache>,
     {
         request.send(self)
     }
 }
+
+/// A wrapper around [`reqwest::RequestBuilder`] that retries requests according to the
+/// [`RetryPolicy`] of the client.
+pub(crate) struct RequestBuilder<'client, TCache> {
+    client: &'client Client<TCache>,
+    inner: reqwest::RequestBuilder,
+    idempotent: bool,
+}
+
+impl<'client, TCache> RequestBuilder<'client, TCache> {
+    pub fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        self.inner = self.inner.json(json);
+        self
+    }
+
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.inner = self.inner.query(query);
+        self
+    }
+
+    pub fn multipart(mut self, form: reqwest::multipart::Form) -> Self {
+        self.inner = self.inner.multipart(form);
+        self
+    }
+
+    /// Sends the request.
+    ///
+    /// If the server responds with `401 Unauthorized`, the access token is refreshed and the
+    /// request is sent again. Idempotent requests are also sent again if the server responds with
+    /// `429 Too Many Requests`, `502 Bad Gateway`, `503 Service Unavailable` or `504 Gateway
+    /// Timeout`, unless the server asks to wait longer than [`RetryPolicy::max_backoff`]. Requests
+    /// with a body that can not be cloned are never sent again.
+    pub async fn send(self) -> Result<Response, RequestResponseError> {
+        let Self {
+            client,
+            inner,
+            idempotent,
+        } = self;
+        let mut has_refreshed_access_token = false;
+        let mut retries = 0;
+        loop {
+            let access_token = client.access_token();
+            let request = match inner.try_clone() {
+                Some(v) => v,
+                None => return Ok(inner.bearer_auth(access_token).send().await?),
+            };
+            let response = request.bearer_auth(&access_token).send().await?;
+            let policy = client.retry_policy();
+            match response.status() {
+                StatusCode::UNAUTHORIZED
+                    if policy.refresh_on_unauthorized && !has_refreshed_access_token =>
+                {
+                    client.refresh_access_token(Some(&access_token)).await?;
+                    has_refreshed_access_token = true;
+                }
+                StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+                    if idempotent && retries < policy.max_retries =>
+                {
+                    let delay = match policy.delay(retries, retry_after(&response)) {
+                        Some(v) => v,
+                        None => return Ok(response),
+                    };
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+}
+
+/// Generates a new attachment key and encrypts it with the key of the owner of the cipher.
+fn generate_attachment_key(key: &SymmetricKey) -> (SymmetricKey, SymmetricEncryptedBytes) {
+    let attachment_key = SymmetricKey::generate();
+    // `unwrap` is safe here because generated keys always have a MAC key
+    let mut attachment_key_bytes = [attachment_key.enc, attachment_key.mac.unwrap()].concat();
+    let encrypted_key = SymmetricEncryptedBytes::encrypt(&attachment_key_bytes, key);
+    attachment_key_bytes.zeroize();
+    (attachment_key, encrypted_key)
+}
+
+/// Encrypts the data of the reader into the buffer and returns a body that streams the encrypted
+/// data together with its length.
+async fn encrypt_to_body<R, B>(
+    reader: &mut R,
+    mut buffer: B,
+    key: &SymmetricKey,
+) -> io::Result<(reqwest::Body, u64)>
+where
+    R: AsyncRead + Unpin,
+    B: AsyncRead + AsyncWrite + AsyncSeek + Unpin + Send + Sync + 'static,
+{
+    // `unwrap` is safe here because the keys of attachments and sends always have a MAC key
+    let params = (key.enc, key.mac.unwrap());
+    let start = buffer.seek(SeekFrom::Current(0)).await?;
+    let length = AesCbc256HmacSha256::encrypt_stream(reader, &mut buffer, &params).await?;
+    buffer.seek(SeekFrom::Start(start)).await?;
+    let stream = ReaderStream::new(buffer.take(length));
+    Ok((reqwest::Body::wrap_stream(stream), length))
+}
+
+/// Returns the key of the owner of a cipher that attachments are encrypted with.
+fn attachment_owner_key<'a>(
+    cipher: &cipher::Cipher,
+    key_ring: &'a KeyRing,
+) -> Result<&'a SymmetricKey, AttachmentError> {
+    match cipher.organization_id {
+        Some(id) => key_ring
+            .organization_key(id)
+            .ok_or(AttachmentError::MissingOrganizationKey(id)),
+        None => Ok(key_ring.user_key()),
+    }
+}
+
+/// Returns the delay of the `Retry-After` header.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// Parses the value of a `Retry-After` header, which contains either a number of seconds or a
+/// date. A date in the past results in no delay.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_header() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:30:00 GMT", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:20:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("-1", now), None);
+        assert_eq!(parse_retry_after("foo", now), None);
+    }
+
+    #[test]
+    fn retry_after_exceeding_max_backoff() {
+        let policy = RetryPolicy::new().with_max_backoff(Duration::from_secs(30));
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(30))),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(31))), None);
+        assert_eq!(policy.delay(0, None), Some(policy.initial_backoff));
+    }
+}
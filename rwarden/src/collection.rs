@@ -1,6 +1,8 @@
 //! Module for collection resources.
 
-use crate::crypto::SymmetricEncryptedString;
+use crate::crypto::{SymmetricEncryptedString, SymmetricKey};
+use crate::util::decrypt_field;
+use crate::{FieldDecryptionError, KeyRing, KeyRingDecryptionError};
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -48,6 +50,47 @@ pub struct Collection {
     pub external_id: Option<Uuid>,
 }
 
+impl Collection {
+    /// Decrypts the collection with the given key.
+    pub fn decrypt(&self, key: &SymmetricKey) -> Result<CollectionView, FieldDecryptionError> {
+        Ok(CollectionView {
+            id: self.id,
+            organization_id: self.organization_id,
+            name: decrypt_field(&self.name, key, || "name".to_owned())?,
+            external_id: self.external_id,
+        })
+    }
+
+    /// Decrypts the collection with the key of the organization.
+    pub fn decrypt_with(
+        &self,
+        key_ring: &KeyRing,
+    ) -> Result<CollectionView, KeyRingDecryptionError> {
+        Ok(self.decrypt(key_ring.key(Some(self.organization_id))?)?)
+    }
+}
+
+/// The decrypted counterpart of [`Collection`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollectionView {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub external_id: Option<Uuid>,
+}
+
+impl CollectionView {
+    /// Encrypts the collection with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Collection {
+        Collection {
+            id: self.id,
+            organization_id: self.organization_id,
+            name: SymmetricEncryptedString::encrypt(&self.name, key),
+            external_id: self.external_id,
+        }
+    }
+}
+
 /// A collection resource with additional information.
 // NOTE: Serialize is only needed for cache
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
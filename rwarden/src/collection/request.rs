@@ -19,7 +19,7 @@ pub struct Get {
 
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
     type Output = BoxFuture<'request, crate::Result<Collection, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -50,7 +50,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetAll
 {
     type Output = BoxStream<'request, crate::Result<Vec<Collection>, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         request_stream! {
             client.request(
                 Method::GET,
@@ -80,7 +80,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Create
 {
     type Output = BoxFuture<'request, crate::Result<Collection, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -116,7 +116,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Delete
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
                 .request(
@@ -134,7 +134,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse_empty()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .delete_collections(std::iter::once(self.collection_id))
                 .await
                 .map_err(Error::Cache)?;
@@ -183,7 +184,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Modify
 {
     type Output = BoxFuture<'request, crate::Result<Collection, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -220,7 +221,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetGroupDetails
 {
     type Output = BoxFuture<'request, crate::Result<CollectionGroupDetails, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -249,11 +250,12 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetAllDetails
 {
     type Output = BoxStream<'request, crate::Result<Vec<CollectionDetails>, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         request_stream! {
             client.request(Method::GET, format!("{}/collections", client.urls().base)).await?,
             response => client
-                .cache_mut()
+                .cache()
+                .await
                 .save_collections(&response.data)
                 .await
                 .map_err(Error::Cache)?
@@ -272,7 +274,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetUsers
 {
     type Output = BoxFuture<'request, crate::Result<Users, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -305,7 +307,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for ModifyUsers
 {
     type Output = BoxFuture<'request, crate::Result<Users, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             Ok(client
                 .request(
@@ -339,7 +341,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for DeleteUser
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
                 .request(
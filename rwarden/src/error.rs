@@ -1,7 +1,10 @@
-use crate::crypto::{symmetric_encryption, SymmetricKeyError};
-use crate::response;
-use std::{error::Error as StdError, fmt};
+use crate::crypto::{
+    asymmetric_encryption, symmetric_encryption, StringDecryptionError, SymmetricKeyError,
+};
+use crate::{response, PendingTwoFactorLogin};
+use std::{error::Error as StdError, fmt, io};
 use thiserror::Error as ThisError;
+use uuid::Uuid;
 
 /// Error that can occur while interacting with the Bitwarden API.
 #[derive(Debug)]
@@ -59,10 +62,25 @@ pub enum LoginError {
     #[error("failed to create symmetric key")]
     CreateSymmetricKey(#[from] SymmetricKeyError),
     /// Two factor authentication is required.
+    ///
+    /// The login can be continued with [`PendingTwoFactorLogin::continue_with`].
     #[error("two factor authentication is required")]
     TwoFactorRequired {
         two_factor_providers: Vec<response::TwoFactorProvider>,
+        pending: Box<PendingTwoFactorLogin>,
     },
+    /// The server did not return a refresh token.
+    #[error("the server did not return a refresh token")]
+    MissingRefreshToken,
+}
+
+impl From<RequestResponseError> for LoginError {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
 }
 
 /// Error type for requests and server responses.
@@ -85,8 +103,331 @@ impl<TCacheError> From<RequestResponseError> for Error<TCacheError> {
     }
 }
 
+/// Error that can occur when accessing the keys of a [`Client`].
+///
+/// [`Client`]: crate::Client
+#[derive(Debug, ThisError)]
+pub enum KeyError {
+    /// The client is locked.
+    #[error("the client is locked")]
+    Locked,
+    /// Failed to create symmetric key.
+    #[error("failed to create symmetric key")]
+    SymmetricKey(#[from] SymmetricKeyError),
+}
+
+/// Error that can occur when changing the master password, the KDF or the email address with
+/// [`Client::change_master_password`], [`Client::change_kdf`] or [`Client::change_email`].
+///
+/// [`Client::change_master_password`]: crate::Client::change_master_password
+/// [`Client::change_kdf`]: crate::Client::change_kdf
+/// [`Client::change_email`]: crate::Client::change_email
+#[derive(Debug, ThisError)]
+pub enum AccountKeyError {
+    /// The master password is not correct.
+    #[error("invalid master password")]
+    InvalidMasterPassword,
+    /// Request error.
+    #[error("request error")]
+    Request(#[from] reqwest::Error),
+    /// Server returned an error.
+    #[error("server returned an error")]
+    Response(#[from] response::Error),
+}
+
+impl From<RequestResponseError> for AccountKeyError {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
+}
+
+/// Error that can occur when rotating the symmetric key with [`Client::rotate_symmetric_key`].
+///
+/// [`Client::rotate_symmetric_key`]: crate::Client::rotate_symmetric_key
+#[derive(Debug)]
+pub enum KeyRotationError<TCacheError> {
+    /// The master password is not correct.
+    InvalidMasterPassword,
+    /// Failed to decrypt a value with the current symmetric key.
+    Decryption(symmetric_encryption::DecryptionError),
+    /// A personal cipher has an attachment without a key.
+    ///
+    /// The data of such attachments is encrypted with the symmetric key of the account directly,
+    /// so they must be re-keyed with [`Client::rekey_attachment`] first.
+    ///
+    /// [`Client::rekey_attachment`]: crate::Client::rekey_attachment
+    LegacyAttachment {
+        cipher_id: Uuid,
+        attachment_id: Uuid,
+    },
+    /// A personal cipher has a type that is not supported by this library.
+    ///
+    /// It is unknown which values of such ciphers must be encrypted again.
+    UnknownCipherType { cipher_id: Uuid },
+    /// Failed to send request.
+    Request(reqwest::Error),
+    /// Server returned an error.
+    Response(response::Error),
+    /// Failed to read or write cache.
+    Cache(TCacheError),
+}
+
+impl<TCacheError> fmt::Display for KeyRotationError<TCacheError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMasterPassword => f.write_str("invalid master password"),
+            Self::Decryption(_) => f.write_str("failed to decrypt value"),
+            Self::LegacyAttachment { .. } => {
+                f.write_str("a cipher has an attachment without a key")
+            }
+            Self::UnknownCipherType { .. } => f.write_str("a cipher has an unknown type"),
+            Self::Request(_) => f.write_str("failed to send request"),
+            Self::Response(_) => f.write_str("server returned an error"),
+            Self::Cache(_) => f.write_str("failed to read or write cache"),
+        }
+    }
+}
+
+impl<TCacheError: StdError + 'static> StdError for KeyRotationError<TCacheError> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::InvalidMasterPassword => None,
+            Self::Decryption(e) => Some(e),
+            Self::LegacyAttachment { .. } => None,
+            Self::UnknownCipherType { .. } => None,
+            Self::Request(e) => Some(e),
+            Self::Response(e) => Some(e),
+            Self::Cache(e) => Some(e),
+        }
+    }
+}
+
+impl<TCacheError> From<symmetric_encryption::DecryptionError> for KeyRotationError<TCacheError> {
+    fn from(error: symmetric_encryption::DecryptionError) -> Self {
+        Self::Decryption(error)
+    }
+}
+
+impl<TCacheError> From<RequestResponseError> for KeyRotationError<TCacheError> {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
+}
+
+impl<TCacheError> From<Error<TCacheError>> for KeyRotationError<TCacheError> {
+    fn from(error: Error<TCacheError>) -> Self {
+        match error {
+            Error::Request(e) => Self::Request(e),
+            Error::Response(e) => Self::Response(e),
+            Error::Cache(e) => Self::Cache(e),
+        }
+    }
+}
+
+/// Error that can occur when generating a key pair with [`Client::generate_key_pair`].
+///
+/// [`Client::generate_key_pair`]: crate::Client::generate_key_pair
+#[derive(Debug, ThisError)]
+pub enum KeyPairError {
+    /// The account already has a key pair.
+    #[error("the account already has a key pair")]
+    AlreadyExists,
+    /// Failed to access the symmetric key.
+    #[error("failed to access the symmetric key")]
+    Key(#[from] KeyError),
+    /// Request error.
+    #[error("request error")]
+    Request(#[from] reqwest::Error),
+    /// Server returned an error.
+    #[error("server returned an error")]
+    Response(#[from] response::Error),
+}
+
+impl From<RequestResponseError> for KeyPairError {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
+}
+
+/// Error that can occur when decrypting the data of an attachment.
+#[derive(Debug, ThisError)]
+pub enum AttachmentDecryptionError {
+    /// Failed to decrypt the key of the attachment.
+    #[error("failed to decrypt the key of the attachment")]
+    Key(#[source] symmetric_encryption::DecryptionError),
+    /// The key of the attachment has an invalid length.
+    #[error("the key of the attachment has an invalid length")]
+    InvalidKeyLength,
+    /// The data is not a valid encrypted file.
+    #[error("the data is not a valid encrypted file")]
+    Parse(#[from] symmetric_encryption::ParseError),
+    /// Failed to decrypt the data.
+    #[error("failed to decrypt the data")]
+    Decryption(#[source] symmetric_encryption::DecryptionError),
+}
+
+/// Error that can occur when downloading or re-keying an attachment.
+#[derive(Debug, ThisError)]
+pub enum AttachmentError {
+    /// The key ring does not contain the key of the organization that owns the cipher.
+    #[error("the key of the organization `{0}` is not available")]
+    MissingOrganizationKey(Uuid),
+    /// Failed to decrypt the attachment.
+    #[error("failed to decrypt the attachment")]
+    Decryption(#[from] AttachmentDecryptionError),
+    /// Request error.
+    #[error("request error")]
+    Request(#[from] reqwest::Error),
+    /// Server returned an error.
+    #[error("server returned an error")]
+    Response(#[from] response::Error),
+    /// Failed to read or write the data.
+    #[error("failed to read or write the data")]
+    Io(#[from] io::Error),
+}
+
+impl From<RequestResponseError> for AttachmentError {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
+}
+
+impl From<symmetric_encryption::StreamDecryptionError> for AttachmentError {
+    fn from(error: symmetric_encryption::StreamDecryptionError) -> Self {
+        use symmetric_encryption::StreamDecryptionError;
+        match error {
+            StreamDecryptionError::Io(e) => Self::Io(e),
+            StreamDecryptionError::Parse(e) => Self::Decryption(AttachmentDecryptionError::Parse(
+                symmetric_encryption::ParseError::AesCbc256HmacSha256(e),
+            )),
+            StreamDecryptionError::Decryption(e) => {
+                Self::Decryption(AttachmentDecryptionError::Decryption(
+                    symmetric_encryption::DecryptionError::AesCbc256HmacSha256(e),
+                ))
+            }
+        }
+    }
+}
+
+/// Error that can occur when creating a file send from a reader.
+#[derive(Debug, ThisError)]
+pub enum FileUploadError {
+    /// Request error.
+    #[error("request error")]
+    Request(#[from] reqwest::Error),
+    /// Server returned an error.
+    #[error("server returned an error")]
+    Response(#[from] response::Error),
+    /// Failed to read or write the data.
+    #[error("failed to read or write the data")]
+    Io(#[from] io::Error),
+}
+
+impl From<RequestResponseError> for FileUploadError {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
+}
+
+/// Error that can occur when creating a [`KeyRing`].
+///
+/// [`KeyRing`]: crate::KeyRing
+#[derive(Debug, ThisError)]
+pub enum KeyRingError {
+    /// Failed to access the symmetric key.
+    #[error("failed to access the symmetric key")]
+    Key(#[from] KeyError),
+    /// Failed to access the private key.
+    #[error("failed to access the private key")]
+    PrivateKey(#[from] PrivateKeyError),
+    /// Failed to decrypt the symmetric key of an organization.
+    #[error("failed to decrypt the symmetric key of an organization")]
+    Decryption(#[from] asymmetric_encryption::DecryptionError),
+    /// The symmetric key of an organization has an invalid length.
+    #[error("the symmetric key of the organization `{0}` has an invalid length")]
+    InvalidLength(Uuid),
+}
+
+/// Error that can occur when decrypting a resource.
+#[derive(Debug, ThisError)]
+#[error("failed to decrypt field `{field}`")]
+pub struct FieldDecryptionError {
+    /// The path of the field that could not be decrypted (e.g. `login.uris[0].uri`).
+    pub field: String,
+    /// The underlying error.
+    pub source: StringDecryptionError<symmetric_encryption::DecryptionError>,
+}
+
+/// Error that can occur when decrypting a value with a [`KeyRing`].
+///
+/// [`KeyRing`]: crate::KeyRing
+#[derive(Debug, ThisError)]
+pub enum KeyRingDecryptionError {
+    /// The key ring does not contain the key of the organization.
+    #[error("the key of the organization `{0}` is not available")]
+    MissingOrganizationKey(Uuid),
+    /// Failed to decrypt the value.
+    #[error("failed to decrypt the value")]
+    Decryption(#[from] StringDecryptionError<symmetric_encryption::DecryptionError>),
+    /// Failed to decrypt a field of the resource.
+    #[error("failed to decrypt the resource")]
+    Field(#[from] FieldDecryptionError),
+}
+
+/// Error that can occur when creating or modifying the keys of an organization.
+#[derive(Debug, ThisError)]
+pub enum OrganizationKeyError {
+    /// Failed to access the private key.
+    #[error("failed to access the private key")]
+    PrivateKey(#[from] PrivateKeyError),
+    /// Failed to decrypt the symmetric key of the organization.
+    #[error("failed to decrypt the symmetric key of the organization")]
+    Decryption(#[from] asymmetric_encryption::DecryptionError),
+    /// The account is not a member of the organization.
+    #[error("the account is not a member of the organization")]
+    OrganizationNotFound,
+    /// The user has not accepted the invitation to the organization yet.
+    #[error("the user has not accepted the invitation yet")]
+    NotAccepted,
+    /// The public key of the user is invalid.
+    #[error("the public key of the user is invalid")]
+    InvalidPublicKey,
+    /// Request error.
+    #[error("request error")]
+    Request(#[from] reqwest::Error),
+    /// Server returned an error.
+    #[error("server returned an error")]
+    Response(#[from] response::Error),
+}
+
+impl From<RequestResponseError> for OrganizationKeyError {
+    fn from(error: RequestResponseError) -> Self {
+        match error {
+            RequestResponseError::Request(e) => Self::Request(e),
+            RequestResponseError::Response(e) => Self::Response(e),
+        }
+    }
+}
+
 #[derive(Debug, ThisError)]
 pub enum PrivateKeyError {
+    #[error("the client is locked")]
+    Locked,
     #[error("failed to create symmetric key")]
     SymmetricKey(#[from] SymmetricKeyError),
     #[error("failed to decrypt private key")]
@@ -96,3 +437,35 @@ pub enum PrivateKeyError {
     #[error("private key is not available")]
     NotAvailable,
 }
+
+impl From<KeyError> for PrivateKeyError {
+    fn from(error: KeyError) -> Self {
+        match error {
+            KeyError::Locked => Self::Locked,
+            KeyError::SymmetricKey(e) => Self::SymmetricKey(e),
+        }
+    }
+}
+
+/// Error that can occur when opening a [`SealedSession`].
+///
+/// [`SealedSession`]: crate::SealedSession
+#[derive(Debug, ThisError)]
+pub enum SessionError {
+    /// The session was sealed with a PIN and must be opened with [`SealedSession::open_with_pin`].
+    ///
+    /// [`SealedSession::open_with_pin`]: crate::SealedSession::open_with_pin
+    #[error("the session was sealed with a PIN")]
+    SealedWithPin,
+    /// The session was not sealed with a PIN and must be opened with [`SealedSession::open`].
+    ///
+    /// [`SealedSession::open`]: crate::SealedSession::open
+    #[error("the session was not sealed with a PIN")]
+    NotSealedWithPin,
+    /// Failed to decrypt the session.
+    #[error("failed to decrypt the session")]
+    Decryption(#[from] symmetric_encryption::DecryptionError),
+    /// Failed to deserialize the session.
+    #[error("failed to deserialize the session")]
+    Deserialization(#[from] serde_json::Error),
+}
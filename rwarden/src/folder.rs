@@ -1,6 +1,10 @@
 //! Module for folder resources.
 
-use crate::crypto::SymmetricEncryptedString;
+use crate::crypto::{
+    symmetric_encryption::DecryptionError, SymmetricEncryptedString, SymmetricKey,
+};
+use crate::util::{self, decrypt_field};
+use crate::{FieldDecryptionError, KeyRing, KeyRingDecryptionError};
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -18,3 +22,47 @@ pub struct Folder {
     pub name: SymmetricEncryptedString,
     pub revision_date: DateTime<FixedOffset>,
 }
+
+/// The decrypted counterpart of [`Folder`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FolderView {
+    pub id: Uuid,
+    pub name: String,
+    pub revision_date: DateTime<FixedOffset>,
+}
+
+impl FolderView {
+    /// Encrypts the folder with the given key.
+    pub fn encrypt(&self, key: &SymmetricKey) -> Folder {
+        Folder {
+            id: self.id,
+            name: SymmetricEncryptedString::encrypt(&self.name, key),
+            revision_date: self.revision_date,
+        }
+    }
+}
+
+impl Folder {
+    /// Decrypts the folder with the given key.
+    pub fn decrypt(&self, key: &SymmetricKey) -> Result<FolderView, FieldDecryptionError> {
+        Ok(FolderView {
+            id: self.id,
+            name: decrypt_field(&self.name, key, || "name".to_owned())?,
+            revision_date: self.revision_date,
+        })
+    }
+
+    /// Decrypts the folder with the key of the user.
+    pub fn decrypt_with(&self, key_ring: &KeyRing) -> Result<FolderView, KeyRingDecryptionError> {
+        Ok(self.decrypt(key_ring.user_key())?)
+    }
+
+    /// Encrypts the name of the folder with a new key.
+    pub(crate) fn reencrypt(
+        &mut self,
+        old_key: &SymmetricKey,
+        new_key: &SymmetricKey,
+    ) -> Result<(), DecryptionError> {
+        util::reencrypt(&mut self.name.0, old_key, new_key)
+    }
+}
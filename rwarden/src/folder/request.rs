@@ -17,7 +17,7 @@ pub struct Get {
 
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
     type Output = BoxFuture<'request, crate::Result<Folder, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -30,7 +30,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_folders(std::iter::once(&value))
                 .await
                 .map_err(Error::Cache)?;
@@ -47,11 +48,12 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetAll
 {
     type Output = BoxStream<'request, crate::Result<Vec<Folder>, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         request_stream! {
             client.request(Method::GET, format!("{}/folders", client.urls().base)).await?,
             response => client
-                .cache_mut()
+                .cache()
+                .await
                 .save_folders(&response.data)
                 .await
                 .map_err(Error::Cache)?
@@ -70,7 +72,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Create
 {
     type Output = BoxFuture<'request, crate::Result<Folder, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(Method::POST, format!("{}/folders", client.urls().base))
@@ -81,7 +83,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_folders(std::iter::once(&value))
                 .await
                 .map_err(Error::Cache)?;
@@ -100,7 +103,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Delete
 {
     type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             client
                 .request(
@@ -113,7 +116,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse_empty()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .delete_folders(std::iter::once(self.id))
                 .await
                 .map_err(Error::Cache)?;
@@ -133,7 +137,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for Modify
 {
     type Output = BoxFuture<'request, crate::Result<Folder, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -149,7 +153,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_folders(std::iter::once(&value))
                 .await
                 .map_err(Error::Cache)?;
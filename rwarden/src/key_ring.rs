@@ -0,0 +1,97 @@
+use crate::crypto::{SymmetricEncryptedString, SymmetricKey};
+use crate::organization::{AccountOrganization, UserStatus};
+use crate::{KeyRingDecryptionError, KeyRingError};
+use rsa::RsaPrivateKey;
+use std::collections::HashMap;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// The symmetric keys of a user and of the organizations of the user.
+///
+/// Resources that are owned by an organization are encrypted with the key of the organization
+/// instead of the key of the user. The key ring returns the correct key for the owner of a
+/// resource, so that personal and organization resources can be decrypted the same way.
+///
+/// A key ring can be created with [`Client::key_ring`].
+///
+/// [`Client::key_ring`]: crate::Client::key_ring
+#[derive(Debug, Clone)]
+pub struct KeyRing {
+    user_key: SymmetricKey,
+    organization_keys: HashMap<Uuid, SymmetricKey>,
+}
+
+impl KeyRing {
+    /// Creates a new [`KeyRing`] that only contains the key of the user.
+    pub fn new(user_key: SymmetricKey) -> Self {
+        Self {
+            user_key,
+            organization_keys: HashMap::new(),
+        }
+    }
+
+    /// Decrypts the keys of the organizations with the private key of the user and adds them to
+    /// the key ring.
+    ///
+    /// Organizations that the user has only been invited to are skipped, because their key is
+    /// not available yet.
+    pub fn insert_organizations(
+        &mut self,
+        private_key: &RsaPrivateKey,
+        organizations: &[AccountOrganization],
+    ) -> Result<(), KeyRingError> {
+        for organization in organizations {
+            if organization.user_status == UserStatus::Invited {
+                continue;
+            }
+            let mut bytes = organization.key.decrypt(private_key)?;
+            let key = SymmetricKey::from_bytes(&bytes)
+                .map_err(|_| KeyRingError::InvalidLength(organization.id));
+            bytes.zeroize();
+            self.organization_keys.insert(organization.id, key?);
+        }
+        Ok(())
+    }
+
+    /// Returns the symmetric key of the user.
+    pub fn user_key(&self) -> &SymmetricKey {
+        &self.user_key
+    }
+
+    /// Returns the symmetric key of an organization.
+    pub fn organization_key(&self, organization_id: Uuid) -> Option<&SymmetricKey> {
+        self.organization_keys.get(&organization_id)
+    }
+
+    /// Returns the key of the owner of a resource.
+    ///
+    /// This is the key of the user if the organization ID is `None`.
+    pub fn get(&self, organization_id: Option<Uuid>) -> Option<&SymmetricKey> {
+        match organization_id {
+            Some(id) => self.organization_key(id),
+            None => Some(&self.user_key),
+        }
+    }
+
+    /// Decrypts a value with the key of its owner.
+    pub fn decrypt(
+        &self,
+        organization_id: Option<Uuid>,
+        value: &SymmetricEncryptedString,
+    ) -> Result<String, KeyRingDecryptionError> {
+        Ok(value.decrypt(self.key(organization_id)?)?)
+    }
+
+    /// Returns the key of the owner of a resource or an error if the key is not available.
+    pub(crate) fn key(
+        &self,
+        organization_id: Option<Uuid>,
+    ) -> Result<&SymmetricKey, KeyRingDecryptionError> {
+        match organization_id {
+            Some(id) => self
+                .organization_key(id)
+                .ok_or(KeyRingDecryptionError::MissingOrganizationKey(id)),
+            None => Ok(&self.user_key),
+        }
+    }
+}
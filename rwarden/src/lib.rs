@@ -1,21 +1,41 @@
 #![warn(rust_2018_idioms, missing_debug_implementations)]
 
 use derive_setters::Setters;
-use serde::{Deserialize, Serialize};
-use serde_repr::Serialize_repr as SerializeRepr;
-use std::{result::Result as StdResult, time::SystemTime};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Certificate, Identity, Proxy,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_repr::{Deserialize_repr as DeserializeRepr, Serialize_repr as SerializeRepr};
+use std::{
+    fmt,
+    result::Result as StdResult,
+    time::{Duration, SystemTime},
+};
 use url::Url;
 use uuid::Uuid;
+use zeroize::Zeroizing;
 
-pub use client::{AnonymousClient, Client, ClientBuilder, LoginResponse};
-pub use error::{Error, LoginError, PrivateKeyError, RequestResponseError};
+pub use client::{
+    AnonymousClient, Client, ClientBuilder, Credentials, LoginResponse, PendingTwoFactorLogin,
+};
+pub use error::{
+    AccountKeyError, AttachmentDecryptionError, AttachmentError, Error, FieldDecryptionError,
+    FileUploadError, KeyError, KeyPairError, KeyRingDecryptionError, KeyRingError,
+    KeyRotationError, LoginError, OrganizationKeyError, PrivateKeyError, RequestResponseError,
+    SessionError,
+};
+pub use key_ring::KeyRing;
 pub use rwarden_crypto as crypto;
+pub use session::{SealedSession, Session, SessionObserver};
 
 #[macro_use]
 mod util;
 
 mod client;
 mod error;
+mod key_ring;
+mod session;
 
 pub mod account;
 pub mod cache;
@@ -24,17 +44,20 @@ pub mod collection;
 pub mod folder;
 pub mod organization;
 pub mod path;
+pub mod policy;
 pub mod response;
+pub mod send;
 pub mod settings;
 pub mod sync;
 pub mod totp;
+pub mod two_factor;
 
 /// Type alias for `Result<TOk, Error<TCacheError>>`.
 pub type Result<TOk, TCacheError> = StdResult<TOk, Error<TCacheError>>;
 
 pub trait Request<'request, 'client, TCache> {
     type Output;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output;
 }
 
 /// Struct for specifying the URLs of API endpoints.
@@ -95,10 +118,181 @@ impl Urls {
     }
 }
 
+/// Configuration of the HTTP client that is used for sending requests.
+///
+/// # Example
+///
+/// ```
+/// # use rwarden::{AnonymousClient, HttpConfig, Urls};
+/// # use reqwest::header::{HeaderName, HeaderValue};
+/// # use std::time::Duration;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let http_config = HttpConfig::new()
+///     .with_timeout(Duration::from_secs(30))
+///     .with_user_agent("my-app")
+///     .with_header(
+///         HeaderName::from_static("cf-access-client-id"),
+///         HeaderValue::from_static("foo"),
+///     );
+/// let urls = Urls::custom("https://example.com")?;
+/// let client = AnonymousClient::with_http_config(urls, &http_config)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Setters)]
+#[setters(strip_option, prefix = "with_")]
+pub struct HttpConfig {
+    /// The timeout of a request, from when the request starts connecting until the response body
+    /// has finished.
+    pub timeout: Option<Duration>,
+    /// The timeout for connecting to the server.
+    pub connect_timeout: Option<Duration>,
+    /// The value of the `User-Agent` header.
+    #[setters(into)]
+    pub user_agent: Option<String>,
+    /// Headers that are sent with every request.
+    pub default_headers: HeaderMap,
+    /// Proxies that are used for sending requests.
+    #[setters(skip)]
+    pub proxies: Vec<Proxy>,
+    /// Additional trusted root certificates.
+    #[setters(skip)]
+    pub root_certificates: Vec<Certificate>,
+    /// The client certificate that is used for TLS client authentication.
+    pub identity: Option<Identity>,
+}
+
+impl HttpConfig {
+    /// Creates a new [`HttpConfig`] with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header that is sent with every request.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Adds a proxy.
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Adds a trusted root certificate.
+    pub fn with_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Builds a [`reqwest::Client`] with this configuration.
+    pub fn build(&self) -> reqwest::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder().default_headers(self.default_headers.clone());
+        if let Some(v) = self.timeout {
+            builder = builder.timeout(v);
+        }
+        if let Some(v) = self.connect_timeout {
+            builder = builder.connect_timeout(v);
+        }
+        if let Some(v) = &self.user_agent {
+            builder = builder.user_agent(v);
+        }
+        for proxy in &self.proxies {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(v) = &self.identity {
+            builder = builder.identity(v.clone());
+        }
+        builder.build()
+    }
+}
+
+/// Policy for retrying requests that failed.
+///
+/// # Example
+///
+/// ```
+/// # use rwarden::RetryPolicy;
+/// # use std::time::Duration;
+/// let retry_policy = RetryPolicy::new()
+///     .with_max_retries(5)
+///     .with_initial_backoff(Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Setters)]
+#[setters(prefix = "with_")]
+pub struct RetryPolicy {
+    /// Whether the access token is refreshed and the request is sent again if the server responds
+    /// with `401 Unauthorized`. Defaults to `true`.
+    pub refresh_on_unauthorized: bool,
+    /// The maximum number of times an idempotent request is sent again if the server is
+    /// rate-limiting or temporarily unavailable. Defaults to `3`.
+    pub max_retries: u32,
+    /// The delay before the first retry. The delay is doubled for every following retry. Defaults
+    /// to 500 milliseconds.
+    ///
+    /// If the server sends a `Retry-After` header, its value is used instead.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two retries. Defaults to 30 seconds.
+    ///
+    /// If the server sends a `Retry-After` header with a longer delay, the request is not sent
+    /// again and the response is returned instead.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a new [`RetryPolicy`] with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`RetryPolicy`] that never retries requests.
+    pub fn none() -> Self {
+        Self {
+            refresh_on_unauthorized: false,
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    fn backoff(&self, retries: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(2u32.saturating_pow(retries))
+            .map_or(self.max_backoff, |v| v.min(self.max_backoff))
+    }
+
+    /// Returns the delay before the next retry, or `None` if the delay of the `Retry-After`
+    /// header exceeds the maximum backoff.
+    fn delay(&self, retries: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after {
+            Some(v) if v > self.max_backoff => None,
+            Some(v) => Some(v),
+            None => Some(self.backoff(retries)),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            refresh_on_unauthorized: true,
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
 /// An access token and its expiry time.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The access token is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct AccessTokenData {
-    pub access_token: String,
+    #[serde(with = "util::zeroizing")]
+    pub access_token: Zeroizing<String>,
     pub expiry_time: SystemTime,
 }
 
@@ -108,8 +302,17 @@ impl AccessTokenData {
     }
 }
 
+impl fmt::Debug for AccessTokenData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessTokenData")
+            .field("access_token", &"<redacted>")
+            .field("expiry_time", &self.expiry_time)
+            .finish()
+    }
+}
+
 /// The type of a device.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SerializeRepr)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeRepr, SerializeRepr)]
 #[repr(u8)]
 pub enum DeviceType {
     Android = 0,
@@ -136,21 +339,84 @@ pub enum DeviceType {
 }
 
 /// The provider for two factor authentication.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SerializeRepr)]
-#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TwoFactorProvider {
-    Authenticator = 0,
-    Email = 1,
-    Duo = 2,
-    YubiKey = 3,
-    U2f = 4,
-    Remember = 5,
-    OrganizationDuo = 6,
-    WebAuthn = 7,
+    Authenticator,
+    Email,
+    Duo,
+    YubiKey,
+    U2f,
+    Remember,
+    OrganizationDuo,
+    WebAuthn,
+    /// A provider that is not supported by this library.
+    Unknown(u8),
+}
+
+impl From<u8> for TwoFactorProvider {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Authenticator,
+            1 => Self::Email,
+            2 => Self::Duo,
+            3 => Self::YubiKey,
+            4 => Self::U2f,
+            5 => Self::Remember,
+            6 => Self::OrganizationDuo,
+            7 => Self::WebAuthn,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+impl From<TwoFactorProvider> for u8 {
+    fn from(value: TwoFactorProvider) -> Self {
+        match value {
+            TwoFactorProvider::Authenticator => 0,
+            TwoFactorProvider::Email => 1,
+            TwoFactorProvider::Duo => 2,
+            TwoFactorProvider::YubiKey => 3,
+            TwoFactorProvider::U2f => 4,
+            TwoFactorProvider::Remember => 5,
+            TwoFactorProvider::OrganizationDuo => 6,
+            TwoFactorProvider::WebAuthn => 7,
+            TwoFactorProvider::Unknown(v) => v,
+        }
+    }
+}
+
+impl Serialize for TwoFactorProvider {
+    fn serialize<S>(&self, serializer: S) -> StdResult<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8((*self).into())
+    }
+}
+
+impl<'de> Deserialize<'de> for TwoFactorProvider {
+    fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u8::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// The location where the data of an attachment or a file send is uploaded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeRepr, SerializeRepr)]
+#[repr(u8)]
+pub enum FileUploadType {
+    /// The data is uploaded to the server.
+    Direct = 0,
+    /// The data is uploaded to Azure blob storage.
+    Azure = 1,
 }
 
 /// Data used for performing logins.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Setters)]
+///
+/// The password and the two factor token are zeroized when the data is dropped.
+#[derive(Clone, PartialEq, Eq, Setters)]
 #[setters(strip_option, prefix = "with_")]
 pub struct LoginData {
     /// The email address.
@@ -158,7 +424,7 @@ pub struct LoginData {
     pub email: String,
     /// The master password.
     #[setters(skip)]
-    pub password: String,
+    pub password: Zeroizing<String>,
     #[setters(skip)]
     pub client_id: String,
     #[setters(into)]
@@ -167,8 +433,8 @@ pub struct LoginData {
     #[setters(into)]
     pub device_push_token: Option<String>,
     pub two_factor_provider: Option<TwoFactorProvider>,
-    #[setters(into)]
-    pub two_factor_token: Option<String>,
+    #[setters(skip)]
+    pub two_factor_token: Option<Zeroizing<String>>,
     pub two_factor_remember: bool,
 }
 
@@ -183,7 +449,7 @@ impl LoginData {
         Self {
             client_id: client_id.into(),
             email: email.into(),
-            password: password.into(),
+            password: Zeroizing::new(password.into()),
             device_name: None,
             device_type: None,
             device_push_token: None,
@@ -192,10 +458,132 @@ impl LoginData {
             two_factor_remember: false,
         }
     }
+
+    /// Sets the two factor token.
+    pub fn with_two_factor_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.two_factor_token = Some(Zeroizing::new(token.into()));
+        self
+    }
+
+    /// Sets a token that was returned in [`LoginResponse::two_factor_remember_token`] by a
+    /// previous login, so that two factor authentication is skipped.
+    pub fn with_two_factor_remember_token<S: Into<String>>(self, token: S) -> Self {
+        self.with_two_factor_provider(TwoFactorProvider::Remember)
+            .with_two_factor_token(token)
+    }
+}
+
+impl fmt::Debug for LoginData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginData")
+            .field("email", &self.email)
+            .field("password", &"<redacted>")
+            .field("client_id", &self.client_id)
+            .field("device_name", &self.device_name)
+            .field("device_type", &self.device_type)
+            .field("device_push_token", &self.device_push_token)
+            .field("two_factor_provider", &self.two_factor_provider)
+            .field(
+                "two_factor_token",
+                &self.two_factor_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("two_factor_remember", &self.two_factor_remember)
+            .finish()
+    }
+}
+
+/// A personal API key.
+///
+/// The client secret is zeroized when the API key is dropped.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize, Setters)]
+#[setters(strip_option, prefix = "with_")]
+pub struct ApiKey {
+    /// The client ID (`user.<uuid>`).
+    #[setters(skip)]
+    pub client_id: String,
+    /// The client secret.
+    #[setters(skip)]
+    #[serde(with = "util::zeroizing")]
+    pub client_secret: Zeroizing<String>,
+    /// The identifier of the device. [`ApiKey::new`] generates a random identifier.
+    #[setters(into)]
+    pub device_identifier: String,
+    #[setters(into)]
+    pub device_name: Option<String>,
+    pub device_type: Option<DeviceType>,
+}
+
+impl ApiKey {
+    /// Creates a new [`ApiKey`].
+    pub fn new<I, S>(client_id: I, client_secret: S) -> Self
+    where
+        I: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            client_id: client_id.into(),
+            client_secret: Zeroizing::new(client_secret.into()),
+            device_identifier: Uuid::new_v4().to_hyphenated().to_string(),
+            device_name: None,
+            device_type: None,
+        }
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .field("device_identifier", &self.device_identifier)
+            .field("device_name", &self.device_name)
+            .field("device_type", &self.device_type)
+            .finish()
+    }
+}
+
+/// Data used for performing logins with a personal API key.
+///
+/// The password is zeroized when the data is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKeyLoginData {
+    /// The email address.
+    pub email: String,
+    /// The master password. It is only used for deriving the source key.
+    pub password: Zeroizing<String>,
+    /// The API key.
+    pub api_key: ApiKey,
+}
+
+impl ApiKeyLoginData {
+    /// Creates a new [`ApiKeyLoginData`].
+    pub fn new<E, P>(email: E, password: P, api_key: ApiKey) -> Self
+    where
+        E: Into<String>,
+        P: Into<String>,
+    {
+        Self {
+            email: email.into(),
+            password: Zeroizing::new(password.into()),
+            api_key,
+        }
+    }
+}
+
+impl fmt::Debug for ApiKeyLoginData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeyLoginData")
+            .field("email", &self.email)
+            .field("password", &"<redacted>")
+            .field("api_key", &self.api_key)
+            .finish()
+    }
 }
 
 /// Data used for registering a user.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Setters)]
+///
+/// The password is zeroized when the data is dropped.
+#[derive(Clone, PartialEq, Eq, Setters)]
 #[setters(strip_option, prefix = "with_")]
 pub struct RegisterData {
     /// The email address.
@@ -203,7 +591,7 @@ pub struct RegisterData {
     pub email: String,
     /// The master password.
     #[setters(skip)]
-    pub password: String,
+    pub password: Zeroizing<String>,
     /// The hint for the master password.
     #[setters(into)]
     pub password_hint: Option<String>,
@@ -212,12 +600,8 @@ pub struct RegisterData {
     pub name: Option<String>,
     /// The ID of an organization that the user will be part of.
     pub organization_user_id: Option<Uuid>,
-    /// The KDF type. Defaults to [`KdfType::Pbkdf2Sha256`].
-    ///
-    /// [`KdfType::Pbkdf2Sha256`]: crypto::KdfType::Pbkdf2Sha256
-    pub kdf_type: Option<crypto::KdfType>,
-    /// The number of KDF iterations. Defaults to `100_000`.
-    pub kdf_iterations: Option<u32>,
+    /// The KDF. Defaults to PBKDF2 SHA-256 with `100_000` iterations.
+    pub kdf: Option<crypto::Kdf>,
 }
 
 impl RegisterData {
@@ -229,12 +613,44 @@ impl RegisterData {
     {
         Self {
             email: email.into(),
-            password: password.into(),
+            password: Zeroizing::new(password.into()),
             password_hint: None,
             name: None,
             organization_user_id: None,
-            kdf_type: None,
-            kdf_iterations: None,
+            kdf: None,
         }
     }
 }
+
+impl fmt::Debug for RegisterData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisterData")
+            .field("email", &self.email)
+            .field("password", &"<redacted>")
+            .field("password_hint", &self.password_hint)
+            .field("name", &self.name)
+            .field("organization_user_id", &self.organization_user_id)
+            .field("kdf", &self.kdf)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_policy_backoff() {
+        let policy = RetryPolicy::new();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(6), Duration::from_secs(30));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(30));
+        let policy = policy
+            .with_initial_backoff(Duration::MAX)
+            .with_max_backoff(Duration::MAX);
+        assert_eq!(policy.backoff(0), Duration::MAX);
+        assert_eq!(policy.backoff(1), Duration::MAX);
+    }
+}
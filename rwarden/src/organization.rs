@@ -1,10 +1,17 @@
 //! Module for organization resources.
 
-use crate::crypto::AsymmetricEncryptedBytes;
+use crate::collection::SelectionReadOnly;
+use crate::crypto::{AsymmetricEncryptedBytes, SymmetricEncryptedBytes};
+use crate::{util, OrganizationKeyError};
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr as DeserializeRepr, Serialize_repr as SerializeRepr};
 use uuid::Uuid;
 
+pub use request::*;
+
+mod request;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeRepr, SerializeRepr)]
 #[repr(u8)]
 pub enum UserType {
@@ -74,3 +81,162 @@ pub struct AccountOrganization {
     pub provider_id: Option<String>,
     pub provider_name: Option<String>,
 }
+
+/// The plan of an organization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeRepr, SerializeRepr)]
+#[repr(u8)]
+pub enum PlanType {
+    Free = 0,
+    FamiliesAnnually2019 = 1,
+    TeamsMonthly2019 = 2,
+    TeamsAnnually2019 = 3,
+    EnterpriseMonthly2019 = 4,
+    EnterpriseAnnually2019 = 5,
+    Custom = 6,
+    FamiliesAnnually = 7,
+    TeamsMonthly = 8,
+    TeamsAnnually = 9,
+    EnterpriseMonthly = 10,
+    EnterpriseAnnually = 11,
+}
+
+// https://github.com/bitwarden/server/blob/v1.40.0/src/Core/Models/Api/Response/OrganizationResponseModel.cs
+/// An organization resource.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Organization {
+    pub id: Uuid,
+    pub identifier: Option<String>,
+    pub name: String,
+    pub business_name: Option<String>,
+    pub billing_email: String,
+    pub plan_type: PlanType,
+    pub seats: Option<u32>,
+    pub max_collections: Option<u32>,
+    pub max_storage_gb: Option<u32>,
+    pub use_policies: bool,
+    pub use_sso: bool,
+    pub use_groups: bool,
+    pub use_directory: bool,
+    pub use_events: bool,
+    pub use_totp: bool,
+    pub use_2fa: bool,
+    pub use_api: bool,
+    #[serde(default)]
+    pub use_reset_password: bool,
+    pub users_get_premium: bool,
+    pub self_host: bool,
+    pub has_public_and_private_keys: bool,
+}
+
+/// The subscription of an organization.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Subscription {
+    #[serde(flatten)]
+    pub organization: Organization,
+    pub storage_name: Option<String>,
+    pub storage_gb: Option<f64>,
+    /// The date when the license of a self-hosted organization expires.
+    pub expiration: Option<DateTime<FixedOffset>>,
+}
+
+/// The key pair of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Keys {
+    /// The base64 encoded public key (SPKI).
+    pub public_key: String,
+    /// The private key (PKCS#8) that is encrypted with the symmetric key of the organization.
+    pub private_key: SymmetricEncryptedBytes,
+}
+
+/// The key pair of a new organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct KeysRequestModel {
+    /// The base64 encoded public key (SPKI).
+    pub public_key: String,
+    /// The private key (PKCS#8) that is encrypted with the symmetric key of the organization.
+    pub encrypted_private_key: SymmetricEncryptedBytes,
+}
+
+// https://github.com/bitwarden/server/blob/v1.40.0/src/Core/Models/Api/Response/OrganizationUserResponseModel.cs
+/// A user of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct User {
+    /// The ID of the organization user.
+    pub id: Uuid,
+    /// The ID of the user, which is `None` if the user has not accepted the invitation yet.
+    pub user_id: Option<Uuid>,
+    pub name: Option<String>,
+    pub email: String,
+    #[serde(rename = "Type")]
+    pub user_type: UserType,
+    pub status: UserStatus,
+    pub access_all: bool,
+    pub permissions: Option<Permissions>,
+    pub two_factor_enabled: bool,
+    #[serde(default)]
+    pub reset_password_enrolled: bool,
+}
+
+/// A user of an organization with the collections that the user can access.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UserDetails {
+    /// The ID of the organization user.
+    pub id: Uuid,
+    /// The ID of the user, which is `None` if the user has not accepted the invitation yet.
+    pub user_id: Option<Uuid>,
+    #[serde(rename = "Type")]
+    pub user_type: UserType,
+    pub status: UserStatus,
+    pub access_all: bool,
+    pub permissions: Option<Permissions>,
+    #[serde(deserialize_with = "util::deserialize_optional")]
+    pub collections: Vec<SelectionReadOnly>,
+}
+
+/// The result of a bulk request for a single organization user.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BulkUserResponse {
+    /// The ID of the organization user.
+    pub id: Uuid,
+    /// The error message, which is empty if the request succeeded for the user.
+    pub error: String,
+}
+
+/// The symmetric key of an organization that is encrypted for a single organization user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConfirmUserKey {
+    /// The ID of the organization user.
+    pub id: Uuid,
+    /// The symmetric key of the organization that is encrypted with the public key of the user.
+    pub key: AsymmetricEncryptedBytes,
+}
+
+/// The result of [`Client::confirm_organization_users`].
+///
+/// [`Client::confirm_organization_users`]: crate::Client::confirm_organization_users
+#[derive(Debug)]
+pub struct ConfirmUsersResponse {
+    /// The results of the users that were sent to the server.
+    pub responses: Vec<BulkUserResponse>,
+    /// The users that were not sent to the server, because the symmetric key of the organization
+    /// could not be encrypted for them.
+    pub failures: Vec<ConfirmUserFailure>,
+}
+
+/// A user that could not be confirmed, because the symmetric key of the organization could not be
+/// encrypted with the public key of the user.
+#[derive(Debug)]
+pub struct ConfirmUserFailure {
+    /// The ID of the organization user.
+    pub id: Uuid,
+    /// The reason why the key could not be encrypted.
+    pub error: OrganizationKeyError,
+}
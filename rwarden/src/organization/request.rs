@@ -0,0 +1,619 @@
+use crate::collection::SelectionReadOnlyRequestModel;
+use crate::crypto::{AsymmetricEncryptedBytes, MasterPasswordHash, SymmetricEncryptedString};
+use crate::organization::{
+    BulkUserResponse, ConfirmUserKey, Keys, KeysRequestModel, Organization, Permissions, PlanType,
+    Subscription, User, UserDetails, UserType,
+};
+use crate::util::{ListResponse, ResponseExt};
+use crate::{cache::Cache, Client, Request, RequestResponseError};
+use futures_core::{future::BoxFuture, stream::BoxStream};
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+/// A [`Request`] for retrieving an organization.
+///
+/// Organizations are created with [`Client::create_organization`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct Get {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
+    type Output = BoxFuture<'request, crate::Result<Organization, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!("{}/organizations/{}", client.urls().base, self.id),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for creating an organization.
+///
+/// [`Client::create_organization`] can be used for generating the keys of the organization and
+/// creating it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct Create {
+    #[builder(setter(into))]
+    pub name: String,
+    #[builder(setter(into))]
+    pub billing_email: String,
+    #[builder(default = PlanType::Free)]
+    pub plan_type: PlanType,
+    /// The symmetric key of the organization that is encrypted with the public key of the user.
+    pub key: AsymmetricEncryptedBytes,
+    /// The name of the default collection.
+    pub collection_name: SymmetricEncryptedString,
+    pub keys: KeysRequestModel,
+}
+
+impl Create {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<Organization, RequestResponseError> {
+        client
+            .request(
+                Method::POST,
+                format!("{}/organizations", client.urls().base),
+            )
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse()
+            .await
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Create
+{
+    type Output = BoxFuture<'request, crate::Result<Organization, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for modifying an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct Modify {
+    #[serde(skip)]
+    pub id: Uuid,
+    #[builder(setter(into))]
+    pub name: String,
+    #[builder(default, setter(into, strip_option))]
+    pub business_name: Option<String>,
+    #[builder(setter(into))]
+    pub billing_email: String,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Modify
+{
+    type Output = BoxFuture<'request, crate::Result<Organization, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::PUT,
+                    format!("{}/organizations/{}", client.urls().base, self.id),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for deleting an organization.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct Delete {
+    pub id: Uuid,
+    pub master_password_hash: MasterPasswordHash,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Delete
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::DELETE,
+                    format!("{}/organizations/{}", client.urls().base, self.id),
+                )
+                .await?
+                .json(&json!({
+                    "MasterPasswordHash": self.master_password_hash,
+                }))
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for leaving an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct Leave {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Leave
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::POST,
+                    format!("{}/organizations/{}/leave", client.urls().base, self.id),
+                )
+                .await?
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for retrieving the key pair of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetKeys {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetKeys
+{
+    type Output = BoxFuture<'request, crate::Result<Keys, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!("{}/organizations/{}/keys", client.urls().base, self.id),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving the subscription of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetSubscription {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetSubscription
+{
+    type Output = BoxFuture<'request, crate::Result<Subscription, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!(
+                        "{}/organizations/{}/subscription",
+                        client.urls().base,
+                        self.id
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving the license of an organization.
+///
+/// The license is returned unchanged, so that it can be uploaded to a self-hosted server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetLicense {
+    pub id: Uuid,
+    pub installation_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetLicense
+{
+    type Output = BoxFuture<'request, crate::Result<Value, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!("{}/organizations/{}/license", client.urls().base, self.id),
+                )
+                .await?
+                .query(&[("installationId", self.installation_id)])
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving a user of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetUser {
+    pub organization_id: Uuid,
+    /// The ID of the organization user.
+    pub user_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetUser
+{
+    type Output = BoxFuture<'request, crate::Result<UserDetails, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!(
+                        "{}/organizations/{}/users/{}",
+                        client.urls().base,
+                        self.organization_id,
+                        self.user_id
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving all users of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetAllUsers {
+    pub organization_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetAllUsers
+{
+    type Output = BoxStream<'request, crate::Result<Vec<User>, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        request_stream! {
+            client.request(
+                Method::GET,
+                format!(
+                    "{}/organizations/{}/users",
+                    client.urls().base,
+                    self.organization_id
+                ),
+            )
+            .await?
+        }
+    }
+}
+
+/// A [`Request`] for inviting users to an organization.
+///
+/// Users that accepted the invitation must be confirmed with [`Client::confirm_organization_user`]
+/// or [`Client::confirm_organization_users`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct InviteUsers {
+    #[serde(skip)]
+    pub organization_id: Uuid,
+    pub emails: Vec<String>,
+    #[serde(rename = "Type")]
+    pub user_type: UserType,
+    #[builder(default)]
+    pub access_all: bool,
+    #[builder(default)]
+    pub collections: Vec<SelectionReadOnlyRequestModel>,
+    #[builder(default, setter(strip_option))]
+    pub permissions: Option<Permissions>,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for InviteUsers
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::POST,
+                    format!(
+                        "{}/organizations/{}/users/invite",
+                        client.urls().base,
+                        self.organization_id
+                    ),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for sending the invitation to an organization again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct ReinviteUser {
+    pub organization_id: Uuid,
+    /// The ID of the organization user.
+    pub user_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for ReinviteUser
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::POST,
+                    format!(
+                        "{}/organizations/{}/users/{}/reinvite",
+                        client.urls().base,
+                        self.organization_id,
+                        self.user_id
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for accepting the invitation to an organization.
+///
+/// The token is contained in the invitation email.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct AcceptInvitation {
+    pub organization_id: Uuid,
+    /// The ID of the organization user.
+    pub user_id: Uuid,
+    #[builder(setter(into))]
+    pub token: String,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for AcceptInvitation
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::POST,
+                    format!(
+                        "{}/organizations/{}/users/{}/accept",
+                        client.urls().base,
+                        self.organization_id,
+                        self.user_id
+                    ),
+                )
+                .await?
+                .json(&json!({ "Token": self.token }))
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for confirming a user that accepted the invitation to an organization.
+///
+/// [`Client::confirm_organization_user`] can be used for encrypting the symmetric key of the
+/// organization with the public key of the user and confirming the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConfirmUser {
+    #[serde(skip)]
+    pub organization_id: Uuid,
+    /// The ID of the organization user.
+    #[serde(skip)]
+    pub user_id: Uuid,
+    /// The symmetric key of the organization that is encrypted with the public key of the user.
+    pub key: AsymmetricEncryptedBytes,
+}
+
+impl ConfirmUser {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<(), RequestResponseError> {
+        client
+            .request(
+                Method::POST,
+                format!(
+                    "{}/organizations/{}/users/{}/confirm",
+                    client.urls().base,
+                    self.organization_id,
+                    self.user_id
+                ),
+            )
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse_empty()
+            .await
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for ConfirmUser
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for confirming multiple users that accepted the invitation to an organization.
+///
+/// [`Client::confirm_organization_users`] can be used for encrypting the symmetric key of the
+/// organization with the public keys of the users and confirming them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConfirmUsers {
+    #[serde(skip)]
+    pub organization_id: Uuid,
+    pub keys: Vec<ConfirmUserKey>,
+}
+
+impl ConfirmUsers {
+    pub(crate) async fn execute<TCache>(
+        &self,
+        client: &Client<TCache>,
+    ) -> Result<Vec<BulkUserResponse>, RequestResponseError> {
+        let response: ListResponse<BulkUserResponse> = client
+            .request(
+                Method::POST,
+                format!(
+                    "{}/organizations/{}/users/confirm",
+                    client.urls().base,
+                    self.organization_id
+                ),
+            )
+            .await?
+            .json(self)
+            .send()
+            .await?
+            .parse()
+            .await?;
+        Ok(response.data)
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for ConfirmUsers
+{
+    type Output = BoxFuture<'request, crate::Result<Vec<BulkUserResponse>, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move { Ok(self.execute(client).await?) })
+    }
+}
+
+/// A [`Request`] for modifying the type, the permissions and the collections of a user of an
+/// organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct ModifyUser {
+    #[serde(skip)]
+    pub organization_id: Uuid,
+    /// The ID of the organization user.
+    #[serde(skip)]
+    pub user_id: Uuid,
+    #[serde(rename = "Type")]
+    pub user_type: UserType,
+    #[builder(default)]
+    pub access_all: bool,
+    #[builder(default)]
+    pub collections: Vec<SelectionReadOnlyRequestModel>,
+    #[builder(default, setter(strip_option))]
+    pub permissions: Option<Permissions>,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for ModifyUser
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::PUT,
+                    format!(
+                        "{}/organizations/{}/users/{}",
+                        client.urls().base,
+                        self.organization_id,
+                        self.user_id
+                    ),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for removing a user from an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct RemoveUser {
+    pub organization_id: Uuid,
+    /// The ID of the organization user.
+    pub user_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for RemoveUser
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::DELETE,
+                    format!(
+                        "{}/organizations/{}/users/{}",
+                        client.urls().base,
+                        self.organization_id,
+                        self.user_id
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
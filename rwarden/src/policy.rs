@@ -0,0 +1,523 @@
+//! Module for policy resources.
+
+use crate::organization::{AccountOrganization, UserStatus, UserType};
+use crate::{cipher, send, sync::Sync};
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use serde_repr::{Deserialize_repr as DeserializeRepr, Serialize_repr as SerializeRepr};
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+use uuid::Uuid;
+
+pub use request::*;
+
+mod request;
+
+/// The type of a policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeRepr, SerializeRepr)]
+#[repr(u8)]
+pub enum PolicyType {
+    TwoFactorAuthentication = 0,
+    MasterPassword = 1,
+    PasswordGenerator = 2,
+    SingleOrganization = 3,
+    RequireSso = 4,
+    PersonalOwnership = 5,
+    DisableSend = 6,
+    SendOptions = 7,
+    ResetPassword = 8,
+    MaximumVaultTimeout = 9,
+    DisablePersonalVaultExport = 10,
+}
+
+/// The requirements for master passwords.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MasterPasswordPolicy {
+    /// The minimum complexity score (0 to 4).
+    pub min_complexity: Option<u8>,
+    pub min_length: Option<u32>,
+    pub require_upper: bool,
+    pub require_lower: bool,
+    pub require_numbers: bool,
+    pub require_special: bool,
+    /// Whether the requirements are checked when logging in.
+    pub enforce_on_login: bool,
+}
+
+impl MasterPasswordPolicy {
+    /// The characters that are counted as special characters.
+    pub const SPECIAL_CHARACTERS: &'static str = "!@#$%^&*";
+
+    /// Combines two policies into a policy that contains the stricter requirements of both.
+    pub fn combine(&self, other: &Self) -> Self {
+        Self {
+            min_complexity: self.min_complexity.max(other.min_complexity),
+            min_length: self.min_length.max(other.min_length),
+            require_upper: self.require_upper || other.require_upper,
+            require_lower: self.require_lower || other.require_lower,
+            require_numbers: self.require_numbers || other.require_numbers,
+            require_special: self.require_special || other.require_special,
+            enforce_on_login: self.enforce_on_login || other.enforce_on_login,
+        }
+    }
+
+    /// Checks whether a master password meets the requirements.
+    ///
+    /// The complexity is the zxcvbn score (0 to 4) of the password, which is not calculated by
+    /// rwarden.
+    pub fn check<P: AsRef<str>>(&self, password: P, complexity: u8) -> Result<(), PolicyViolation> {
+        let password = password.as_ref();
+        if let Some(min_complexity) = self.min_complexity {
+            if complexity < min_complexity {
+                return Err(PolicyViolation::MasterPasswordTooWeak { min_complexity });
+            }
+        }
+        if let Some(min_length) = self.min_length {
+            if password.chars().count() < min_length as usize {
+                return Err(PolicyViolation::MasterPasswordTooShort { min_length });
+            }
+        }
+        if self.require_upper && !password.chars().any(char::is_uppercase) {
+            return Err(PolicyViolation::MasterPasswordMissingUppercase);
+        }
+        if self.require_lower && !password.chars().any(char::is_lowercase) {
+            return Err(PolicyViolation::MasterPasswordMissingLowercase);
+        }
+        if self.require_numbers && !password.chars().any(|v| v.is_ascii_digit()) {
+            return Err(PolicyViolation::MasterPasswordMissingNumber);
+        }
+        if self.require_special
+            && !password
+                .chars()
+                .any(|v| Self::SPECIAL_CHARACTERS.contains(v))
+        {
+            return Err(PolicyViolation::MasterPasswordMissingSpecial);
+        }
+        Ok(())
+    }
+}
+
+/// The rules for the password generator.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PasswordGeneratorPolicy {
+    /// The default type of generated passwords (`password` or `passphrase`).
+    pub default_type: Option<String>,
+    pub min_length: Option<u32>,
+    pub use_upper: bool,
+    pub use_lower: bool,
+    pub use_numbers: bool,
+    pub use_special: bool,
+    pub min_numbers: Option<u32>,
+    pub min_special: Option<u32>,
+    pub min_number_words: Option<u32>,
+    pub capitalize: bool,
+    pub include_number: bool,
+}
+
+impl PasswordGeneratorPolicy {
+    /// Combines two policies into a policy that contains the stricter rules of both.
+    ///
+    /// The default type of the first policy is preferred.
+    pub fn combine(&self, other: &Self) -> Self {
+        Self {
+            default_type: self
+                .default_type
+                .clone()
+                .or_else(|| other.default_type.clone()),
+            min_length: self.min_length.max(other.min_length),
+            use_upper: self.use_upper || other.use_upper,
+            use_lower: self.use_lower || other.use_lower,
+            use_numbers: self.use_numbers || other.use_numbers,
+            use_special: self.use_special || other.use_special,
+            min_numbers: self.min_numbers.max(other.min_numbers),
+            min_special: self.min_special.max(other.min_special),
+            min_number_words: self.min_number_words.max(other.min_number_words),
+            capitalize: self.capitalize || other.capitalize,
+            include_number: self.include_number || other.include_number,
+        }
+    }
+}
+
+/// The options for sends.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SendOptionsPolicy {
+    /// Whether hiding the email address of the creator of a send is forbidden.
+    pub disable_hide_email: bool,
+}
+
+/// The options for the account recovery.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ResetPasswordPolicy {
+    /// Whether new users are automatically enrolled in the account recovery.
+    pub auto_enroll_enabled: bool,
+}
+
+/// The maximum vault timeout.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MaximumVaultTimeoutPolicy {
+    pub minutes: u32,
+    /// The action that is performed when the vault times out (`lock` or `logOut`).
+    pub action: Option<String>,
+}
+
+/// The type of a policy and its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    TwoFactorAuthentication,
+    MasterPassword(MasterPasswordPolicy),
+    PasswordGenerator(PasswordGeneratorPolicy),
+    SingleOrganization,
+    RequireSso,
+    PersonalOwnership,
+    DisableSend,
+    SendOptions(SendOptionsPolicy),
+    ResetPassword(ResetPasswordPolicy),
+    MaximumVaultTimeout(MaximumVaultTimeoutPolicy),
+    DisablePersonalVaultExport,
+    /// A policy type that is not known to rwarden.
+    Unknown {
+        id: u8,
+        data: Option<Value>,
+    },
+}
+
+impl Type {
+    /// Returns the numeric ID of the policy type.
+    pub fn id(&self) -> u8 {
+        if let Self::Unknown { id, .. } = self {
+            return *id;
+        }
+        // `unwrap` is safe here because only unknown policy types have no `PolicyType`
+        self.policy_type().unwrap() as u8
+    }
+
+    /// Returns the [`PolicyType`] or `None` if the policy type is not known.
+    pub fn policy_type(&self) -> Option<PolicyType> {
+        Some(match self {
+            Self::TwoFactorAuthentication => PolicyType::TwoFactorAuthentication,
+            Self::MasterPassword(_) => PolicyType::MasterPassword,
+            Self::PasswordGenerator(_) => PolicyType::PasswordGenerator,
+            Self::SingleOrganization => PolicyType::SingleOrganization,
+            Self::RequireSso => PolicyType::RequireSso,
+            Self::PersonalOwnership => PolicyType::PersonalOwnership,
+            Self::DisableSend => PolicyType::DisableSend,
+            Self::SendOptions(_) => PolicyType::SendOptions,
+            Self::ResetPassword(_) => PolicyType::ResetPassword,
+            Self::MaximumVaultTimeout(_) => PolicyType::MaximumVaultTimeout,
+            Self::DisablePersonalVaultExport => PolicyType::DisablePersonalVaultExport,
+            Self::Unknown { .. } => return None,
+        })
+    }
+}
+
+impl Hash for Type {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // `Value` does not implement `Hash`, so the serialized type is hashed instead
+        serde_json::to_string(self).unwrap_or_default().hash(state);
+    }
+}
+
+impl Serialize for Type {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Type", 2)?;
+        state.serialize_field("Type", &self.id())?;
+        match self {
+            Self::MasterPassword(v) => state.serialize_field("Data", v)?,
+            Self::PasswordGenerator(v) => state.serialize_field("Data", v)?,
+            Self::SendOptions(v) => state.serialize_field("Data", v)?,
+            Self::ResetPassword(v) => state.serialize_field("Data", v)?,
+            Self::MaximumVaultTimeout(v) => state.serialize_field("Data", v)?,
+            Self::Unknown { data, .. } => state.serialize_field("Data", data)?,
+            _ => state.serialize_field("Data", &None::<Value>)?,
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Type {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Inner {
+            #[serde(rename = "Type")]
+            ty: u8,
+            data: Option<Value>,
+        }
+
+        fn data<'de, T, E>(value: Option<Value>) -> Result<T, E>
+        where
+            T: Deserialize<'de> + Default,
+            E: de::Error,
+        {
+            match value {
+                Some(v) => T::deserialize(v).map_err(E::custom),
+                None => Ok(T::default()),
+            }
+        }
+
+        let v = Inner::deserialize(deserializer)?;
+        Ok(match v.ty {
+            0 => Self::TwoFactorAuthentication,
+            1 => Self::MasterPassword(data(v.data)?),
+            2 => Self::PasswordGenerator(data(v.data)?),
+            3 => Self::SingleOrganization,
+            4 => Self::RequireSso,
+            5 => Self::PersonalOwnership,
+            6 => Self::DisableSend,
+            7 => Self::SendOptions(data(v.data)?),
+            8 => Self::ResetPassword(data(v.data)?),
+            9 => Self::MaximumVaultTimeout(data(v.data)?),
+            10 => Self::DisablePersonalVaultExport,
+            id => Self::Unknown { id, data: v.data },
+        })
+    }
+}
+
+// https://github.com/bitwarden/server/blob/v1.40.0/src/Core/Models/Api/Response/PolicyResponseModel.cs
+/// A policy resource.
+// NOTE: Serialize is only needed for cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Policy {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    #[serde(flatten)]
+    pub ty: Type,
+    pub enabled: bool,
+}
+
+impl Policy {
+    /// Returns whether the policy applies to a user that is a member of the given organizations.
+    ///
+    /// Owners and admins are exempt from most policies, but the master password requirements and
+    /// the password generator rules apply to all members and the maximum vault timeout only
+    /// exempts owners.
+    pub fn applies_to(&self, organizations: &[AccountOrganization]) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let organization = match organizations.iter().find(|v| v.id == self.organization_id) {
+            Some(v) => v,
+            None => return false,
+        };
+        if !organization.enabled
+            || !organization.use_policies
+            || organization.user_status == UserStatus::Invited
+        {
+            return false;
+        }
+        match self.ty {
+            Type::MasterPassword(_) | Type::PasswordGenerator(_) => true,
+            Type::MaximumVaultTimeout(_) => organization.user_type != UserType::Owner,
+            _ => match organization.user_type {
+                UserType::Owner | UserType::Admin => false,
+                UserType::Custom => !organization
+                    .permissions
+                    .as_ref()
+                    .map(|v| v.manage_policies)
+                    .unwrap_or(false),
+                UserType::User | UserType::Manager => true,
+            },
+        }
+    }
+}
+
+/// A violation of a policy.
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum PolicyViolation {
+    #[error("the master password must have a complexity score of at least {min_complexity}")]
+    MasterPasswordTooWeak { min_complexity: u8 },
+    #[error("the master password must be at least {min_length} characters long")]
+    MasterPasswordTooShort { min_length: u32 },
+    #[error("the master password must contain an uppercase character")]
+    MasterPasswordMissingUppercase,
+    #[error("the master password must contain a lowercase character")]
+    MasterPasswordMissingLowercase,
+    #[error("the master password must contain a number")]
+    MasterPasswordMissingNumber,
+    #[error("the master password must contain a special character")]
+    MasterPasswordMissingSpecial,
+    #[error("ciphers must be owned by an organization")]
+    PersonalOwnership,
+    #[error("sends are disabled")]
+    SendDisabled,
+    #[error("hiding the email address of a send is disabled")]
+    SendHideEmailDisabled,
+}
+
+/// The policies that apply to the user.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EnforcedPolicies {
+    policies: Vec<Policy>,
+}
+
+impl EnforcedPolicies {
+    /// Creates a new [`EnforcedPolicies`] from the policies that apply to a user that is a member
+    /// of the given organizations.
+    pub fn new<I>(policies: I, organizations: &[AccountOrganization]) -> Self
+    where
+        I: IntoIterator<Item = Policy>,
+    {
+        Self {
+            policies: policies
+                .into_iter()
+                .filter(|v| v.applies_to(organizations))
+                .collect(),
+        }
+    }
+
+    /// Creates a new [`EnforcedPolicies`] from a synchronization response.
+    pub fn from_sync(sync: &Sync) -> Self {
+        Self::new(sync.policies.iter().cloned(), &sync.account.organizations)
+    }
+
+    /// Returns an iterator over the policies.
+    pub fn iter(&self) -> impl Iterator<Item = &Policy> {
+        self.policies.iter()
+    }
+
+    fn contains(&self, policy_type: PolicyType) -> bool {
+        self.policies
+            .iter()
+            .any(|v| v.ty.policy_type() == Some(policy_type))
+    }
+
+    /// Returns the combined requirements for master passwords.
+    pub fn master_password(&self) -> Option<MasterPasswordPolicy> {
+        self.policies
+            .iter()
+            .filter_map(|v| match &v.ty {
+                Type::MasterPassword(v) => Some(*v),
+                _ => None,
+            })
+            .reduce(|a, b| a.combine(&b))
+    }
+
+    /// Returns the combined rules for the password generator.
+    pub fn password_generator(&self) -> Option<PasswordGeneratorPolicy> {
+        self.policies
+            .iter()
+            .filter_map(|v| match &v.ty {
+                Type::PasswordGenerator(v) => Some(v.clone()),
+                _ => None,
+            })
+            .reduce(|a, b| a.combine(&b))
+    }
+
+    /// Returns the shortest maximum vault timeout.
+    pub fn maximum_vault_timeout(&self) -> Option<Duration> {
+        self.policies
+            .iter()
+            .filter_map(|v| match &v.ty {
+                Type::MaximumVaultTimeout(v) => Some(v.minutes),
+                _ => None,
+            })
+            .min()
+            .map(|v| Duration::from_secs(u64::from(v) * 60))
+    }
+
+    /// Checks whether a master password meets the requirements.
+    ///
+    /// See [`MasterPasswordPolicy::check`] for the complexity score.
+    pub fn check_master_password<P: AsRef<str>>(
+        &self,
+        password: P,
+        complexity: u8,
+    ) -> Result<(), PolicyViolation> {
+        match self.master_password() {
+            Some(v) => v.check(password, complexity),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks whether a cipher can be created or modified.
+    pub fn check_cipher(
+        &self,
+        request_model: &cipher::RequestModel,
+    ) -> Result<(), PolicyViolation> {
+        if request_model.organization_id.is_none() && self.contains(PolicyType::PersonalOwnership) {
+            return Err(PolicyViolation::PersonalOwnership);
+        }
+        Ok(())
+    }
+
+    /// Checks whether a send can be created or modified.
+    pub fn check_send(&self, request_model: &send::RequestModel) -> Result<(), PolicyViolation> {
+        if self.contains(PolicyType::DisableSend) {
+            return Err(PolicyViolation::SendDisabled);
+        }
+        let disable_hide_email = self.policies.iter().any(|v| match &v.ty {
+            Type::SendOptions(v) => v.disable_hide_email,
+            _ => false,
+        });
+        if request_model.hide_email && disable_hide_email {
+            return Err(PolicyViolation::SendHideEmailDisabled);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn master_password_check() {
+        let a = MasterPasswordPolicy {
+            min_length: Some(12),
+            require_upper: true,
+            ..Default::default()
+        };
+        let b = MasterPasswordPolicy {
+            min_complexity: Some(3),
+            min_length: Some(8),
+            require_special: true,
+            ..Default::default()
+        };
+        let policy = a.combine(&b);
+        assert_eq!(
+            policy.check("Short!", 4),
+            Err(PolicyViolation::MasterPasswordTooShort { min_length: 12 })
+        );
+        assert_eq!(
+            policy.check("Long enough password!", 2),
+            Err(PolicyViolation::MasterPasswordTooWeak { min_complexity: 3 })
+        );
+        assert_eq!(
+            policy.check("long enough password!", 4),
+            Err(PolicyViolation::MasterPasswordMissingUppercase)
+        );
+        assert_eq!(
+            policy.check("Long enough password", 4),
+            Err(PolicyViolation::MasterPasswordMissingSpecial)
+        );
+        assert_eq!(policy.check("Long enough password!", 4), Ok(()));
+    }
+
+    #[test]
+    fn deserialize_unknown_type() {
+        let value = serde_json::json!({
+            "Id": "7f1c3b8a-2f9e-4a51-9d6c-0c6c0f6b1a2e",
+            "OrganizationId": "1d5f0e4b-9c0a-4a8e-8f0e-3b7b4d2c6a1f",
+            "Type": 200,
+            "Data": { "foo": "bar" },
+            "Enabled": true,
+        });
+        let policy: Policy = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(policy.ty.id(), 200);
+        assert!(matches!(policy.ty, Type::Unknown { .. }));
+        assert_eq!(serde_json::to_value(&policy).unwrap(), value);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::policy::{Policy, PolicyType, Type};
+use crate::{cache::Cache, util::ResponseExt, Client, Error, Request};
+use futures_core::{future::BoxFuture, stream::BoxStream};
+use reqwest::Method;
+use serde::Serialize;
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+/// A [`Request`] for retrieving a policy of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct Get {
+    pub organization_id: Uuid,
+    pub policy_type: PolicyType,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
+    type Output = BoxFuture<'request, crate::Result<Policy, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::GET,
+                    format!(
+                        "{}/organizations/{}/policies/{}",
+                        client.urls().base,
+                        self.organization_id,
+                        self.policy_type as u8
+                    ),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving all policies of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct GetAll {
+    pub organization_id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetAll
+{
+    type Output = BoxStream<'request, crate::Result<Vec<Policy>, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        request_stream! {
+            client.request(
+                Method::GET,
+                format!(
+                    "{}/organizations/{}/policies",
+                    client.urls().base,
+                    self.organization_id
+                ),
+            )
+            .await?,
+            response => client
+                .cache()
+                .await
+                .save_policies(&response.data)
+                .await
+                .map_err(Error::Cache)?
+        }
+    }
+}
+
+/// A [`Request`] for modifying a policy of an organization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct Modify {
+    #[serde(skip)]
+    pub organization_id: Uuid,
+    #[serde(flatten)]
+    pub ty: Type,
+    pub enabled: bool,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Modify
+{
+    type Output = BoxFuture<'request, crate::Result<Policy, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::PUT,
+                    format!(
+                        "{}/organizations/{}/policies/{}",
+                        client.urls().base,
+                        self.organization_id,
+                        self.ty.id()
+                    ),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
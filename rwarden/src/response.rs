@@ -1,7 +1,8 @@
 //! Module for responses returned from the Bitwarden API.
 
 use serde::{de, Deserialize, Deserializer};
-use std::{collections::HashMap, fmt};
+use std::hash::{Hash, Hasher};
+use std::{collections::HashMap, fmt, mem};
 use thiserror::Error;
 
 #[derive(Deserialize)]
@@ -48,14 +49,52 @@ pub struct Error {
 }
 
 /// Provider for two factor authentication.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TwoFactorProvider {
     Authenticator,
-    Email { email: String },
-    Duo { host: String, signature: String },
-    YubiKey { nfc: bool },
-    U2f { challenges: Vec<U2fChallenge> },
+    Email {
+        email: String,
+    },
+    Duo {
+        host: String,
+        signature: String,
+    },
+    YubiKey {
+        nfc: bool,
+    },
+    U2f {
+        challenges: Vec<U2fChallenge>,
+    },
+    OrganizationDuo {
+        host: String,
+        signature: String,
+    },
     WebAuthn,
+    /// A provider that is not supported by this library.
+    ///
+    /// The value contains the raw JSON of the provider data.
+    Unknown {
+        id: u8,
+        value: serde_json::Value,
+    },
+}
+
+impl Hash for TwoFactorProvider {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Self::Authenticator | Self::WebAuthn => {}
+            Self::Email { email } => email.hash(state),
+            Self::Duo { host, signature } | Self::OrganizationDuo { host, signature } => {
+                host.hash(state);
+                signature.hash(state);
+            }
+            Self::YubiKey { nfc } => nfc.hash(state),
+            Self::U2f { challenges } => challenges.hash(state),
+            // `serde_json::Value` does not implement `Hash`, so the value is left out
+            Self::Unknown { id, .. } => id.hash(state),
+        }
+    }
 }
 
 impl From<&TwoFactorProvider> for crate::TwoFactorProvider {
@@ -66,7 +105,9 @@ impl From<&TwoFactorProvider> for crate::TwoFactorProvider {
             TwoFactorProvider::Duo { .. } => Self::Duo,
             TwoFactorProvider::YubiKey { .. } => Self::YubiKey,
             TwoFactorProvider::U2f { .. } => Self::U2f,
+            TwoFactorProvider::OrganizationDuo { .. } => Self::OrganizationDuo,
             TwoFactorProvider::WebAuthn => Self::WebAuthn,
+            TwoFactorProvider::Unknown { id, .. } => Self::from(*id),
         }
     }
 }
@@ -80,6 +121,13 @@ impl From<TwoFactorProvider> for crate::TwoFactorProvider {
 #[derive(Clone)]
 struct TwoFactorProviderMap(Vec<TwoFactorProvider>);
 
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DuoResponse {
+    host: String,
+    signature: String,
+}
+
 impl<'de> Deserialize<'de> for TwoFactorProviderMap {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -115,13 +163,7 @@ impl<'de> Deserialize<'de> for TwoFactorProviderMap {
                             TwoFactorProvider::Email { email: value.email }
                         }
                         "2" => {
-                            #[derive(Deserialize)]
-                            #[serde(rename_all = "PascalCase")]
-                            struct Response {
-                                host: String,
-                                signature: String,
-                            }
-                            let value = map.next_value::<Response>()?;
+                            let value = map.next_value::<DuoResponse>()?;
                             TwoFactorProvider::Duo {
                                 host: value.host,
                                 signature: value.signature,
@@ -147,13 +189,26 @@ impl<'de> Deserialize<'de> for TwoFactorProviderMap {
                                 challenges: value.challenges,
                             }
                         }
-                        "7" => TwoFactorProvider::WebAuthn,
-                        _ => {
-                            return Err(de::Error::invalid_value(
-                                de::Unexpected::Str(key),
-                                &"a valid two factor provider id",
-                            ))
+                        "6" => {
+                            let value = map.next_value::<DuoResponse>()?;
+                            TwoFactorProvider::OrganizationDuo {
+                                host: value.host,
+                                signature: value.signature,
+                            }
+                        }
+                        "7" => {
+                            let _value = map.next_value::<serde_json::Value>()?;
+                            TwoFactorProvider::WebAuthn
                         }
+                        _ => TwoFactorProvider::Unknown {
+                            id: key.parse().map_err(|_| {
+                                de::Error::invalid_value(
+                                    de::Unexpected::Str(key),
+                                    &"a two factor provider ID",
+                                )
+                            })?,
+                            value: map.next_value()?,
+                        },
                     };
                     providers.push(provider);
                 }
@@ -174,3 +229,37 @@ pub struct U2fChallenge {
     pub version: String,
     pub key_handle: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_factor_provider_map() {
+        let map: TwoFactorProviderMap = serde_json::from_str(
+            r#"{"0":null,"1":{"Email":"foo@example.com"},"5":null,"42":{"Foo":"bar"}}"#,
+        )
+        .unwrap();
+        let providers = map
+            .0
+            .iter()
+            .map(crate::TwoFactorProvider::from)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            providers,
+            [
+                crate::TwoFactorProvider::Authenticator,
+                crate::TwoFactorProvider::Email,
+                crate::TwoFactorProvider::Remember,
+                crate::TwoFactorProvider::Unknown(42),
+            ]
+        );
+        assert_eq!(
+            map.0[3],
+            TwoFactorProvider::Unknown {
+                id: 42,
+                value: serde_json::json!({ "Foo": "bar" }),
+            }
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Module for send resources.
+
+use crate::crypto::{
+    symmetric_encryption::DecryptionError, SendKey, SendKeyError, SymmetricEncryptedBytes,
+    SymmetricEncryptedString, SymmetricKey,
+};
+use crate::util;
+use chrono::{DateTime, FixedOffset};
+use derive_setters::Setters;
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+pub use request::*;
+
+mod request;
+
+/// The text of a text send.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Text {
+    /// The text.
+    pub text: Option<SymmetricEncryptedString>,
+    /// Whether the text is hidden by default when the send is accessed.
+    pub hidden: bool,
+}
+
+/// The file of a file send.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct File {
+    pub id: String,
+    pub file_name: SymmetricEncryptedString,
+    pub size: String,
+    pub size_name: String,
+}
+
+/// The type of a send.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Text(Text),
+    File(File),
+}
+
+impl Serialize for Type {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Type", 2)?;
+        match self {
+            Self::Text(v) => {
+                state.serialize_field("Type", &0)?;
+                state.serialize_field("Text", &v)?;
+            }
+            Self::File(v) => {
+                state.serialize_field("Type", &1)?;
+                state.serialize_field("File", &v)?;
+            }
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Type {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Inner {
+            #[serde(rename = "Type")]
+            ty: i32,
+            text: Option<Text>,
+            file: Option<File>,
+        }
+
+        let v = Inner::deserialize(deserializer)?;
+        match v.ty {
+            0 => {
+                let v = v
+                    .text
+                    .ok_or_else(|| de::Error::custom("Text must not be null"))?;
+                Ok(Self::Text(v))
+            }
+            1 => {
+                let v = v
+                    .file
+                    .ok_or_else(|| de::Error::custom("File must not be null"))?;
+                Ok(Self::File(v))
+            }
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Signed(v.ty.into()),
+                &"one of `0` or `1`",
+            )),
+        }
+    }
+}
+
+/// The type of a send in a [`RequestModel`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RequestType {
+    Text(Text),
+    File {
+        /// The file name.
+        file_name: SymmetricEncryptedString,
+    },
+}
+
+impl From<Type> for RequestType {
+    fn from(ty: Type) -> Self {
+        match ty {
+            Type::Text(v) => Self::Text(v),
+            Type::File(v) => Self::File {
+                file_name: v.file_name,
+            },
+        }
+    }
+}
+
+impl Serialize for RequestType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct File<'a> {
+            file_name: &'a SymmetricEncryptedString,
+        }
+
+        let mut state = serializer.serialize_struct("RequestType", 2)?;
+        match self {
+            Self::Text(v) => {
+                state.serialize_field("Type", &0)?;
+                state.serialize_field("Text", &v)?;
+            }
+            Self::File { file_name } => {
+                state.serialize_field("Type", &1)?;
+                state.serialize_field("File", &File { file_name })?;
+            }
+        }
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Setters, Serialize)]
+#[setters(strip_option, prefix = "with_")]
+#[serde(rename_all = "PascalCase")]
+pub struct RequestModel {
+    #[setters(skip)]
+    pub name: SymmetricEncryptedString,
+    #[setters(skip)]
+    #[serde(flatten)]
+    pub ty: RequestType,
+    /// The key material of the send that is encrypted with the symmetric key of the user.
+    #[setters(skip)]
+    pub key: SymmetricEncryptedBytes,
+    #[setters(skip)]
+    pub deletion_date: DateTime<FixedOffset>,
+    pub notes: Option<SymmetricEncryptedString>,
+    pub max_access_count: Option<u32>,
+    pub expiration_date: Option<DateTime<FixedOffset>>,
+    /// The password hash that is created with [`SendKey::hash_password`].
+    ///
+    /// If this is `None` when modifying a send, the password of the send is not changed.
+    pub password: Option<String>,
+    pub disabled: bool,
+    pub hide_email: bool,
+}
+
+impl RequestModel {
+    pub fn new(
+        name: SymmetricEncryptedString,
+        ty: RequestType,
+        key: SymmetricEncryptedBytes,
+        deletion_date: DateTime<FixedOffset>,
+    ) -> Self {
+        Self {
+            name,
+            ty,
+            key,
+            deletion_date,
+            notes: None,
+            max_access_count: None,
+            expiration_date: None,
+            password: None,
+            disabled: false,
+            hide_email: false,
+        }
+    }
+}
+
+impl From<Send> for RequestModel {
+    fn from(send: Send) -> Self {
+        Self {
+            name: send.name,
+            ty: send.ty.into(),
+            key: send.key,
+            deletion_date: send.deletion_date,
+            notes: send.notes,
+            max_access_count: send.max_access_count,
+            expiration_date: send.expiration_date,
+            password: None,
+            disabled: send.disabled,
+            hide_email: send.hide_email,
+        }
+    }
+}
+
+// https://github.com/bitwarden/server/blob/v1.40.0/src/Core/Models/Api/Response/SendResponseModel.cs
+/// A send resource.
+// NOTE: Serialize is only needed for cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Send {
+    pub id: Uuid,
+    pub access_id: String,
+    pub name: SymmetricEncryptedString,
+    #[serde(flatten)]
+    pub ty: Type,
+    pub notes: Option<SymmetricEncryptedString>,
+    /// The key material of the send that is encrypted with the symmetric key of the user.
+    pub key: SymmetricEncryptedBytes,
+    pub max_access_count: Option<u32>,
+    pub access_count: u32,
+    /// The hash of the password if the send is protected by a password.
+    pub password: Option<String>,
+    pub disabled: bool,
+    #[serde(default)]
+    pub hide_email: bool,
+    pub revision_date: DateTime<FixedOffset>,
+    pub expiration_date: Option<DateTime<FixedOffset>>,
+    pub deletion_date: DateTime<FixedOffset>,
+}
+
+impl Send {
+    /// Decrypts the key material of the send with the symmetric key of the user.
+    ///
+    /// The key that is used for encrypting the data of the send can be derived with
+    /// [`SendKey::stretch`] and the key material is also used in the URL of the send (see
+    /// [`SendKey::encode`]).
+    pub fn send_key(&self, symmetric_key: &SymmetricKey) -> Result<SendKey, SendKeyError> {
+        SendKey::new(symmetric_key, &self.key)
+    }
+
+    /// Encrypts the key material of the send with a new key.
+    ///
+    /// The data of the send is encrypted with the key that is derived from the key material, so
+    /// only the key material needs to be encrypted again.
+    pub(crate) fn reencrypt(
+        &mut self,
+        old_key: &SymmetricKey,
+        new_key: &SymmetricKey,
+    ) -> Result<(), DecryptionError> {
+        util::reencrypt(&mut self.key, old_key, new_key)
+    }
+}
+
+/// A send that is accessed with [`AnonymousClient::access_send`].
+///
+/// [`AnonymousClient::access_send`]: crate::AnonymousClient::access_send
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SendAccess {
+    /// The encoded ID of the send.
+    pub id: String,
+    pub name: SymmetricEncryptedString,
+    #[serde(flatten)]
+    pub ty: Type,
+    pub expiration_date: Option<DateTime<FixedOffset>>,
+    /// The email address of the creator of the send, if it is not hidden.
+    pub creator_identifier: Option<String>,
+}
@@ -0,0 +1,264 @@
+use crate::send::{RequestModel, Send, Type};
+use crate::util::ResponseExt;
+use crate::{cache::Cache, Client, Error, FileUploadType, Request, RequestResponseError};
+use futures_core::{future::BoxFuture, stream::BoxStream};
+use reqwest::{Body, Method};
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use uuid::Uuid;
+
+/// A [`Request`] for retrieving a send.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct Get {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for Get
+{
+    type Output = BoxFuture<'request, crate::Result<Send, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::GET,
+                    format!("{}/sends/{}", client.urls().base, self.id),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?;
+            client
+                .cache()
+                .await
+                .save_sends(std::iter::once(&value))
+                .await
+                .map_err(Error::Cache)?;
+            Ok(value)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving all sends.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GetAll;
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for GetAll
+{
+    type Output = BoxStream<'request, crate::Result<Vec<Send>, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        request_stream! {
+            client.request(Method::GET, format!("{}/sends", client.urls().base)).await?,
+            response => client
+                .cache()
+                .await
+                .save_sends(&response.data)
+                .await
+                .map_err(Error::Cache)?
+        }
+    }
+}
+
+/// A [`Request`] for creating a text send.
+///
+/// File sends are created with [`CreateFile`].
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct Create {
+    pub request_model: RequestModel,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for Create
+{
+    type Output = BoxFuture<'request, crate::Result<Send, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(Method::POST, format!("{}/sends", client.urls().base))
+                .await?
+                .json(&self.request_model)
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for creating a file send.
+///
+/// The file data must already be encrypted with the key that is derived from the key material of
+/// the send (see [`SendKey::stretch`]).
+///
+/// [`SendKey::stretch`]: crate::crypto::SendKey::stretch
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct CreateFile {
+    pub request_model: RequestModel,
+    /// The encrypted file data.
+    pub data: Vec<u8>,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for CreateFile
+{
+    type Output = BoxFuture<'request, crate::Result<Send, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let body = self.data.clone().into();
+            let length = self.data.len() as u64;
+            Ok(create_file(client, &self.request_model, body, length).await?)
+        })
+    }
+}
+
+/// Creates a file send and uploads the encrypted file data.
+pub(crate) async fn create_file<TCache>(
+    client: &Client<TCache>,
+    request_model: &RequestModel,
+    body: Body,
+    length: u64,
+) -> Result<Send, RequestResponseError> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Request<'a> {
+        #[serde(flatten)]
+        request_model: &'a RequestModel,
+        file_length: u64,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Response {
+        url: String,
+        file_upload_type: FileUploadType,
+        send_response: Send,
+    }
+
+    let request = Request {
+        request_model,
+        file_length: length,
+    };
+    let response: Response = client
+        .request(
+            Method::POST,
+            format!("{}/sends/file/v2", client.urls().base),
+        )
+        .await?
+        .json(&request)
+        .send()
+        .await?
+        .parse()
+        .await?;
+    let send = response.send_response;
+    if let Type::File(file) = &send.ty {
+        client
+            .upload_file(
+                response.file_upload_type,
+                format!("{}/sends/{}/file/{}", client.urls().base, send.id, file.id),
+                &response.url,
+                file.file_name.to_string(),
+                body,
+                length,
+            )
+            .await?;
+    }
+    Ok(send)
+}
+
+/// A [`Request`] for modifying a send.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct Modify {
+    pub id: Uuid,
+    pub request_model: RequestModel,
+}
+
+impl Modify {
+    pub fn inherit(send: Send) -> ModifyBuilder<((Uuid,), (RequestModel,))> {
+        ModifyBuilder {
+            fields: ((send.id,), (RequestModel::from(send),)),
+            phantom: (),
+        }
+    }
+}
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for Modify
+{
+    type Output = BoxFuture<'request, crate::Result<Send, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::PUT,
+                    format!("{}/sends/{}", client.urls().base, self.id),
+                )
+                .await?
+                .json(&self.request_model)
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for removing the password of a send.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct RemovePassword {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for RemovePassword
+{
+    type Output = BoxFuture<'request, crate::Result<Send, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            Ok(client
+                .request(
+                    Method::PUT,
+                    format!("{}/sends/{}/remove-password", client.urls().base, self.id),
+                )
+                .await?
+                .send()
+                .await?
+                .parse()
+                .await?)
+        })
+    }
+}
+
+/// A [`Request`] for deleting a send.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+pub struct Delete {
+    pub id: Uuid,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + std::marker::Send>
+    Request<'request, 'client, TCache> for Delete
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::DELETE,
+                    format!("{}/sends/{}", client.urls().base, self.id),
+                )
+                .await?
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            client
+                .cache()
+                .await
+                .delete_sends(std::iter::once(self.id))
+                .await
+                .map_err(Error::Cache)?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use crate::crypto::{Kdf, SourceKey, SymmetricEncryptedBytes, SymmetricKey};
+use crate::{cache::Cache, AccessTokenData, Client, Credentials, SessionError, Urls};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+const PIN_KDF_ITERATIONS: u32 = 100_000;
+
+/// A snapshot of the state of a [`Client`].
+///
+/// The session can be serialized and later be used for creating a new [`Client`] with
+/// [`Session::into_client`], so that no new login is required. Since it contains the source key
+/// and the credentials, it should be sealed with [`Session::seal`] or [`Session::seal_with_pin`]
+/// before it is stored.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Session {
+    pub urls: Urls,
+    pub email: String,
+    pub kdf: Kdf,
+    /// The source key. This is `None` if the client was locked.
+    pub source_key: Option<SourceKey>,
+    pub encrypted_symmetric_key: SymmetricEncryptedBytes,
+    pub encrypted_private_key: Option<SymmetricEncryptedBytes>,
+    pub credentials: Credentials,
+    pub access_token_data: Option<AccessTokenData>,
+}
+
+impl Session {
+    /// Creates a new [`Client`] from the session.
+    pub fn into_client<TCache: Cache>(self, cache: TCache) -> Client<TCache> {
+        self.into_client_with_http_client(cache, reqwest::Client::new())
+    }
+
+    /// Creates a new [`Client`] from the session that uses the given HTTP client.
+    pub fn into_client_with_http_client<TCache: Cache>(
+        self,
+        cache: TCache,
+        http_client: reqwest::Client,
+    ) -> Client<TCache> {
+        Client::builder()
+            .http_client(http_client)
+            .cache(cache)
+            .urls(self.urls)
+            .email(self.email)
+            .kdf(self.kdf)
+            .source_key(self.source_key)
+            .encrypted_symmetric_key(self.encrypted_symmetric_key)
+            .encrypted_private_key(self.encrypted_private_key)
+            .credentials(self.credentials)
+            .access_token_data(self.access_token_data)
+            .build()
+    }
+
+    /// Encrypts the session with the given key.
+    pub fn seal(&self, key: &SymmetricKey) -> SealedSession {
+        SealedSession {
+            pin_kdf: None,
+            data: SymmetricEncryptedBytes::encrypt(&*self.to_json(), key),
+        }
+    }
+
+    /// Encrypts the session with a key that is derived from the given PIN.
+    ///
+    /// The key is derived on tokio's blocking thread pool.
+    pub async fn seal_with_pin<P: AsRef<[u8]>>(&self, pin: P) -> SealedSession {
+        let pin_kdf = PinKdf {
+            salt: Uuid::new_v4().to_hyphenated().to_string(),
+            iterations: PIN_KDF_ITERATIONS,
+        };
+        let key = pin_kdf.derive(pin).await;
+        SealedSession {
+            pin_kdf: Some(pin_kdf),
+            data: SymmetricEncryptedBytes::encrypt(&*self.to_json(), &key),
+        }
+    }
+
+    fn to_json(&self) -> Zeroizing<Vec<u8>> {
+        // `unwrap` is safe here because all fields can be serialized to JSON
+        Zeroizing::new(serde_json::to_vec(self).unwrap())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct PinKdf {
+    salt: String,
+    iterations: u32,
+}
+
+impl PinKdf {
+    async fn derive<P: AsRef<[u8]>>(&self, pin: P) -> SymmetricKey {
+        let kdf = Kdf::pbkdf2_sha256(self.iterations);
+        SourceKey::derive(&self.salt, pin, &kdf).await.stretch()
+    }
+}
+
+/// An encrypted [`Session`].
+///
+/// A sealed session can be created with [`Session::seal`] or [`Session::seal_with_pin`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SealedSession {
+    pin_kdf: Option<PinKdf>,
+    data: SymmetricEncryptedBytes,
+}
+
+impl SealedSession {
+    /// Returns `true` if the session was sealed with [`Session::seal_with_pin`].
+    pub fn is_sealed_with_pin(&self) -> bool {
+        self.pin_kdf.is_some()
+    }
+
+    /// Decrypts a session that was sealed with [`Session::seal`].
+    pub fn open(&self, key: &SymmetricKey) -> Result<Session, SessionError> {
+        if self.pin_kdf.is_some() {
+            return Err(SessionError::SealedWithPin);
+        }
+        self.decrypt(key)
+    }
+
+    /// Decrypts a session that was sealed with [`Session::seal_with_pin`].
+    ///
+    /// The key is derived on tokio's blocking thread pool.
+    pub async fn open_with_pin<P: AsRef<[u8]>>(&self, pin: P) -> Result<Session, SessionError> {
+        let key = match &self.pin_kdf {
+            Some(v) => v.derive(pin).await,
+            None => return Err(SessionError::NotSealedWithPin),
+        };
+        self.decrypt(&key)
+    }
+
+    fn decrypt(&self, key: &SymmetricKey) -> Result<Session, SessionError> {
+        let data = Zeroizing::new(self.data.decrypt(key)?);
+        Ok(serde_json::from_slice(&data)?)
+    }
+}
+
+/// A function that is called whenever the [`Session`] of a [`Client`] changes.
+///
+/// This can be used for keeping a stored session up to date, e.g. after the access token was
+/// refreshed.
+#[derive(Clone)]
+pub struct SessionObserver(Arc<dyn Fn(&Session) + Send + Sync>);
+
+impl SessionObserver {
+    /// Creates a new [`SessionObserver`].
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Session) + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn notify(&self, session: &Session) {
+        (self.0)(session)
+    }
+}
+
+impl fmt::Debug for SessionObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SessionObserver").finish()
+    }
+}
@@ -13,7 +13,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for GetDomains
 {
     type Output = BoxFuture<'request, crate::Result<Domains, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -26,7 +26,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_domains(&value)
                 .await
                 .map_err(Error::Cache)?;
@@ -70,7 +71,7 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
     for ModifyDomains
 {
     type Output = BoxFuture<'request, crate::Result<Domains, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(
@@ -84,7 +85,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .save_domains(&value)
                 .await
                 .map_err(Error::Cache)?;
@@ -2,7 +2,7 @@
 
 use crate::{
     account::Account, cipher::CipherDetails, collection::CollectionDetails, folder::Folder,
-    settings::Domains,
+    policy::Policy, send, settings::Domains, util,
 };
 use serde::Deserialize;
 
@@ -19,7 +19,9 @@ pub struct Sync {
     pub folders: Vec<Folder>,
     pub collections: Vec<CollectionDetails>,
     pub ciphers: Vec<CipherDetails>,
-    // pub policies: Vec<Policy>,
-    // pub sends: Vec<Send>,
+    #[serde(default, deserialize_with = "util::deserialize_optional")]
+    pub policies: Vec<Policy>,
+    #[serde(default, deserialize_with = "util::deserialize_optional")]
+    pub sends: Vec<send::Send>,
     pub domains: Domains,
 }
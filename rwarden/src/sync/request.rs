@@ -8,7 +8,7 @@ pub struct Get;
 
 impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache> for Get {
     type Output = BoxFuture<'request, crate::Result<Sync, TCache::Error>>;
-    fn send(&'request self, client: &'client mut Client<TCache>) -> Self::Output {
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
         Box::pin(async move {
             let value = client
                 .request(Method::GET, format!("{}/sync", client.urls().base))
@@ -19,7 +19,8 @@ impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'clien
                 .parse()
                 .await?;
             client
-                .cache_mut()
+                .cache()
+                .await
                 .sync(&value)
                 .await
                 .map_err(Error::Cache)?;
@@ -0,0 +1,73 @@
+//! Module for two factor authentication.
+
+use crate::TwoFactorProvider;
+use serde::{Deserialize, Serialize};
+
+pub use request::*;
+
+mod request;
+
+/// The status of a provider for two factor authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProviderStatus {
+    /// The provider.
+    #[serde(rename = "Type")]
+    pub provider: TwoFactorProvider,
+    /// Whether the provider is enabled.
+    pub enabled: bool,
+}
+
+/// The settings of the authenticator (TOTP) provider.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Authenticator {
+    /// Whether the provider is enabled.
+    pub enabled: bool,
+    /// The base32 encoded secret key.
+    ///
+    /// If the provider is not enabled, this is a newly generated key that can be used for
+    /// enabling the provider.
+    pub key: String,
+}
+
+/// The settings of the email provider.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Email {
+    /// Whether the provider is enabled.
+    pub enabled: bool,
+    /// The email address that the tokens are sent to.
+    pub email: Option<String>,
+}
+
+/// A recovery code that can be used for disabling two factor authentication when all providers
+/// are unavailable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecoveryCode {
+    /// The recovery code.
+    pub code: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_status_unknown_provider() {
+        let value = serde_json::json!([
+            { "Type": 0, "Enabled": true },
+            { "Type": 5, "Enabled": true },
+            { "Type": 42, "Enabled": false },
+        ]);
+        let providers: Vec<ProviderStatus> = serde_json::from_value(value).unwrap();
+        assert_eq!(providers[0].provider, TwoFactorProvider::Authenticator);
+        assert_eq!(providers[1].provider, TwoFactorProvider::Remember);
+        assert_eq!(providers[2].provider, TwoFactorProvider::Unknown(42));
+        assert_eq!(
+            serde_json::to_value(&providers[2]).unwrap(),
+            serde_json::json!({ "Type": 42, "Enabled": false })
+        );
+    }
+}
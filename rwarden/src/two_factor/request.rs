@@ -0,0 +1,260 @@
+use crate::crypto::MasterPasswordHash;
+use crate::two_factor::{Authenticator, Email, ProviderStatus, RecoveryCode};
+use crate::util::{ListResponse, ResponseExt};
+use crate::{cache::Cache, Client, Request, TwoFactorProvider};
+use futures_core::future::BoxFuture;
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::json;
+use typed_builder::TypedBuilder;
+
+/// A [`Request`] for retrieving the status of all providers for two factor authentication.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GetAll;
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetAll
+{
+    type Output = BoxFuture<'request, crate::Result<Vec<ProviderStatus>, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(Method::GET, format!("{}/two-factor", client.urls().base))
+                .await?
+                .send()
+                .await?
+                .parse::<ListResponse<_>>()
+                .await?;
+            Ok(value.data)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving the settings of the authenticator (TOTP) provider.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct GetAuthenticator {
+    pub master_password_hash: MasterPasswordHash,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetAuthenticator
+{
+    type Output = BoxFuture<'request, crate::Result<Authenticator, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::POST,
+                    format!("{}/two-factor/get-authenticator", client.urls().base),
+                )
+                .await?
+                .json(&json!({
+                    "MasterPasswordHash": self.master_password_hash,
+                }))
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok(value)
+        })
+    }
+}
+
+/// A [`Request`] for enabling the authenticator (TOTP) provider.
+///
+/// The key can be retrieved with [`GetAuthenticator`] and the token must be generated with this
+/// key (e.g. with [`TotpConfig`]).
+///
+/// [`TotpConfig`]: crate::totp::TotpConfig
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnableAuthenticator {
+    pub master_password_hash: MasterPasswordHash,
+    #[builder(setter(into))]
+    pub key: String,
+    #[builder(setter(into))]
+    pub token: String,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for EnableAuthenticator
+{
+    type Output = BoxFuture<'request, crate::Result<Authenticator, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::PUT,
+                    format!("{}/two-factor/authenticator", client.urls().base),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok(value)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving the settings of the email provider.
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct GetEmail {
+    pub master_password_hash: MasterPasswordHash,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetEmail
+{
+    type Output = BoxFuture<'request, crate::Result<Email, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::POST,
+                    format!("{}/two-factor/get-email", client.urls().base),
+                )
+                .await?
+                .json(&json!({
+                    "MasterPasswordHash": self.master_password_hash,
+                }))
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok(value)
+        })
+    }
+}
+
+/// A [`Request`] for sending a token to an email address that can be used for enabling the email
+/// provider with [`EnableEmail`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct SendEmail {
+    pub master_password_hash: MasterPasswordHash,
+    #[builder(setter(into))]
+    pub email: String,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for SendEmail
+{
+    type Output = BoxFuture<'request, crate::Result<(), TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            client
+                .request(
+                    Method::POST,
+                    format!("{}/two-factor/send-email", client.urls().base),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse_empty()
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// A [`Request`] for enabling the email provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnableEmail {
+    pub master_password_hash: MasterPasswordHash,
+    #[builder(setter(into))]
+    pub email: String,
+    #[builder(setter(into))]
+    pub token: String,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for EnableEmail
+{
+    type Output = BoxFuture<'request, crate::Result<Email, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::PUT,
+                    format!("{}/two-factor/email", client.urls().base),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok(value)
+        })
+    }
+}
+
+/// A [`Request`] for disabling a provider for two factor authentication.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TypedBuilder)]
+#[serde(rename_all = "PascalCase")]
+pub struct Disable {
+    pub master_password_hash: MasterPasswordHash,
+    #[serde(rename = "Type")]
+    pub provider: TwoFactorProvider,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for Disable
+{
+    type Output = BoxFuture<'request, crate::Result<ProviderStatus, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::PUT,
+                    format!("{}/two-factor/disable", client.urls().base),
+                )
+                .await?
+                .json(self)
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok(value)
+        })
+    }
+}
+
+/// A [`Request`] for retrieving the recovery code.
+///
+/// The recovery code can be used with [`AnonymousClient::recover_two_factor`] for disabling two
+/// factor authentication when all providers are unavailable.
+///
+/// [`AnonymousClient::recover_two_factor`]: crate::AnonymousClient::recover_two_factor
+#[derive(Debug, Clone, PartialEq, Eq, TypedBuilder)]
+pub struct GetRecoveryCode {
+    pub master_password_hash: MasterPasswordHash,
+}
+
+impl<'request, 'client: 'request, TCache: Cache + Send> Request<'request, 'client, TCache>
+    for GetRecoveryCode
+{
+    type Output = BoxFuture<'request, crate::Result<RecoveryCode, TCache::Error>>;
+    fn send(&'request self, client: &'client Client<TCache>) -> Self::Output {
+        Box::pin(async move {
+            let value = client
+                .request(
+                    Method::POST,
+                    format!("{}/two-factor/get-recover", client.urls().base),
+                )
+                .await?
+                .json(&json!({
+                    "MasterPasswordHash": self.master_password_hash,
+                }))
+                .send()
+                .await?
+                .parse()
+                .await?;
+            Ok(value)
+        })
+    }
+}
@@ -1,6 +1,10 @@
-use crate::{response, LoginError, RequestResponseError};
+use crate::crypto::{
+    symmetric_encryption, SymmetricEncryptedBytes, SymmetricEncryptedString, SymmetricKey,
+};
+use crate::{response, FieldDecryptionError, LoginError, RequestResponseError};
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use zeroize::Zeroize;
 
 pub fn deserialize_optional<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
@@ -11,11 +15,29 @@ where
     Ok(value.unwrap_or_default())
 }
 
+pub mod zeroizing {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use zeroize::Zeroizing;
+
+    pub fn serialize<S: Serializer>(
+        value: &Zeroizing<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_str().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Zeroizing<String>, D::Error> {
+        String::deserialize(deserializer).map(Zeroizing::new)
+    }
+}
+
 #[async_trait]
 pub trait ResponseExt {
     async fn parse<T: DeserializeOwned>(self) -> Result<T, RequestResponseError>;
     async fn parse_empty(self) -> Result<(), RequestResponseError>;
-    async fn parse_with_login_result<T: DeserializeOwned>(self) -> Result<T, LoginError>;
+    async fn parse_token<T: DeserializeOwned>(self) -> Result<TokenResult<T>, LoginError>;
 }
 
 #[async_trait]
@@ -38,21 +60,77 @@ impl ResponseExt for reqwest::Response {
         }
     }
 
-    async fn parse_with_login_result<T: DeserializeOwned>(self) -> Result<T, LoginError> {
+    async fn parse_token<T: DeserializeOwned>(self) -> Result<TokenResult<T>, LoginError> {
         if self.status().is_success() {
-            Ok(self.json().await?)
+            Ok(TokenResult::Success(self.json().await?))
         } else {
             let e = self.json::<response::InnerError>().await?;
-            Err(match e.two_factor_providers() {
-                Some(v) => LoginError::TwoFactorRequired {
-                    two_factor_providers: v,
-                },
-                None => response::Error::from(e).into(),
-            })
+            match e.two_factor_providers() {
+                Some(v) => Ok(TokenResult::TwoFactorRequired(v)),
+                None => Err(response::Error::from(e).into()),
+            }
         }
     }
 }
 
+/// Decrypts the value with the old key and encrypts it with the new key.
+pub fn reencrypt(
+    value: &mut SymmetricEncryptedBytes,
+    old_key: &SymmetricKey,
+    new_key: &SymmetricKey,
+) -> Result<(), symmetric_encryption::DecryptionError> {
+    let mut plaintext = value.decrypt(old_key)?;
+    *value = SymmetricEncryptedBytes::encrypt(&plaintext, new_key);
+    plaintext.zeroize();
+    Ok(())
+}
+
+/// Decrypts a value and names the field in the error.
+pub fn decrypt_field<F>(
+    value: &SymmetricEncryptedString,
+    key: &SymmetricKey,
+    field: F,
+) -> Result<String, FieldDecryptionError>
+where
+    F: FnOnce() -> String,
+{
+    value.decrypt(key).map_err(|source| FieldDecryptionError {
+        field: field(),
+        source,
+    })
+}
+
+/// Decrypts an optional value and names the field in the error.
+pub fn decrypt_optional_field<F>(
+    value: &Option<SymmetricEncryptedString>,
+    key: &SymmetricKey,
+    field: F,
+) -> Result<Option<String>, FieldDecryptionError>
+where
+    F: FnOnce() -> String,
+{
+    value
+        .as_ref()
+        .map(|v| decrypt_field(v, key, field))
+        .transpose()
+}
+
+/// Encrypts an optional value.
+pub fn encrypt_optional(
+    value: &Option<String>,
+    key: &SymmetricKey,
+) -> Option<SymmetricEncryptedString> {
+    value
+        .as_ref()
+        .map(|v| SymmetricEncryptedString::encrypt(v, key))
+}
+
+/// The result of a token request that may require two factor authentication.
+pub enum TokenResult<T> {
+    Success(T),
+    TwoFactorRequired(Vec<response::TwoFactorProvider>),
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ListResponse<T> {
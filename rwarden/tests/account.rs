@@ -2,16 +2,26 @@ mod common;
 
 use rand::{distributions::Alphanumeric, Rng};
 use rwarden::account::{self, Account};
+use rwarden::cipher;
+use rwarden::{cache::EmptyCache, crypto::Kdf, LoginData};
+use rwarden::{AccountKeyError, KeyRotationError};
 
 #[tokio::test]
 async fn account_get() {
-    let mut client = common::login().await.unwrap();
+    let client = common::login().await.unwrap();
     let _account: Account = client.send(&account::Get).await.unwrap();
 }
 
+#[tokio::test]
+async fn account_get_revision_date() {
+    let client = common::login().await.unwrap();
+    let revision_date = client.send(&account::GetRevisionDate).await.unwrap();
+    assert!(revision_date <= chrono::Utc::now());
+}
+
 #[tokio::test]
 async fn account_modify_profile() {
-    let mut client = common::login().await.unwrap();
+    let client = common::login().await.unwrap();
     let random_string: String = rand::thread_rng()
         .sample_iter(&Alphanumeric)
         .take(30)
@@ -33,6 +43,125 @@ async fn account_modify_profile() {
     assert_eq!(account.master_password_hint, Some(master_password_hint));
 }
 
-// TODO: Test modify email
-// TODO: Test modify password
-// TODO: Test modify kdf
+#[tokio::test]
+async fn account_change_master_password_with_invalid_password() {
+    let client = common::login().await.unwrap();
+    let result = client
+        .change_master_password("invalid password", "new password", None::<String>)
+        .await;
+    assert!(matches!(
+        result,
+        Err(AccountKeyError::InvalidMasterPassword)
+    ));
+    assert!(!client.is_locked());
+}
+
+#[tokio::test]
+async fn account_rotate_symmetric_key_with_invalid_password() {
+    let client = common::login().await.unwrap();
+    let result = tokio::spawn(async move { client.rotate_symmetric_key("invalid password").await })
+        .await
+        .unwrap();
+    assert!(matches!(
+        result,
+        Err(KeyRotationError::InvalidMasterPassword)
+    ));
+}
+
+#[tokio::test]
+#[ignore] // This test interferes with some other tests
+async fn account_rotate_symmetric_key_with_attachment() {
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
+    let data = b"-----BEGIN CERTIFICATE-----".to_vec();
+    let cipher = client
+        .create_attachment(
+            &created_cipher,
+            "cert.pem",
+            &data,
+            &client.key_ring(&[]).unwrap(),
+        )
+        .await
+        .unwrap();
+    let attachment_id = cipher.attachments[0].id;
+    client.rotate_symmetric_key(common::PASSWORD).await.unwrap();
+
+    let client = common::login().await.unwrap();
+    let key_ring = client.key_ring(&[]).unwrap();
+    let cipher = client.send(&cipher::Get { id: cipher.id }).await.unwrap();
+    let attachment = client
+        .send(&cipher::GetAttachment {
+            cipher_id: cipher.id,
+            attachment_id,
+        })
+        .await
+        .unwrap();
+    let downloaded_data = client
+        .download_attachment(&cipher, &attachment, &key_ring)
+        .await
+        .unwrap();
+    assert_eq!(downloaded_data, data);
+}
+
+#[tokio::test]
+#[ignore] // This test interferes with some other tests
+async fn account_modify_password() {
+    let new_password = "rwarden-test-password";
+    let client = common::login().await.unwrap();
+    client
+        .change_master_password(common::PASSWORD, new_password, None::<String>)
+        .await
+        .unwrap();
+
+    let login_data = LoginData::new(common::EMAIL, new_password, "desktop");
+    let client = common::client()
+        .login(&login_data, EmptyCache)
+        .await
+        .unwrap()
+        .client;
+    client.symmetric_key().unwrap();
+    client
+        .change_master_password(new_password, common::PASSWORD, None::<String>)
+        .await
+        .unwrap();
+    common::login().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore] // This test interferes with some other tests
+async fn account_modify_kdf() {
+    let client = common::login().await.unwrap();
+    let kdf = client.kdf();
+    let new_kdf = Kdf::argon2id(3, 64, 4).unwrap();
+    client.change_kdf(common::PASSWORD, new_kdf).await.unwrap();
+
+    let client = common::login().await.unwrap();
+    assert_eq!(client.kdf(), new_kdf);
+    client.symmetric_key().unwrap();
+    client.change_kdf(common::PASSWORD, kdf).await.unwrap();
+    assert_eq!(common::login().await.unwrap().kdf(), kdf);
+}
+
+#[tokio::test]
+#[ignore] // This test requires a token that was sent to the new email address
+async fn account_modify_email() {
+    let new_email = std::env::var("RWARDEN_NEW_EMAIL").unwrap();
+    let token = std::env::var("RWARDEN_EMAIL_TOKEN").unwrap();
+    let client = common::login().await.unwrap();
+    client
+        .change_email(common::PASSWORD, &new_email, token)
+        .await
+        .unwrap();
+    assert_eq!(client.email(), new_email);
+
+    let login_data = LoginData::new(&new_email, common::PASSWORD, "desktop");
+    let client = common::client()
+        .login(&login_data, EmptyCache)
+        .await
+        .unwrap()
+        .client;
+    client.symmetric_key().unwrap();
+    let account = client.send(&account::Get).await.unwrap();
+    assert_eq!(account.email, new_email);
+    // The email address is not changed back, since that requires another token
+}
@@ -1,8 +1,12 @@
 mod common;
 
 use futures_util::TryStreamExt;
-use rwarden::cipher::{self, Cipher, CipherDetails, Field, FieldType, RequestModel};
-use rwarden::crypto::{KdfType, MasterPasswordHash, SourceKey, SymmetricEncryptedString};
+use rwarden::cipher::{
+    self, Cipher, CipherDetails, Field, FieldType, FieldView, LoginUriMatchType, LoginUriView,
+    RequestModel, TypeView,
+};
+use rwarden::crypto::{MasterPasswordHash, SourceKey, SymmetricEncryptedString, SymmetricKey};
+use std::io::Cursor;
 
 fn assert_eq_cipher_except_revision_date(a: &Cipher, b: &Cipher) {
     let Cipher {
@@ -40,14 +44,14 @@ fn assert_eq_cipher_except_revision_date(a: &Cipher, b: &Cipher) {
 
 #[tokio::test]
 async fn cipher_create() {
-    let mut client = common::login().await.unwrap();
-    common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    common::create_default_cipher(&client).await.unwrap();
 }
 
 #[tokio::test]
 async fn cipher_get() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
     let retrieved_cipher = client
         .send(&cipher::Get {
             id: created_cipher.id,
@@ -59,8 +63,8 @@ async fn cipher_get() {
 
 #[tokio::test]
 async fn cipher_soft_delete() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
     client
         .send(&cipher::Delete {
             id: created_cipher.id,
@@ -79,8 +83,8 @@ async fn cipher_soft_delete() {
 
 #[tokio::test]
 async fn cipher_hard_delete() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
     client
         .send(&cipher::Delete {
             id: created_cipher.id,
@@ -99,9 +103,9 @@ async fn cipher_hard_delete() {
 
 #[tokio::test]
 async fn cipher_bulk_soft_delete() {
-    let mut client = common::login().await.unwrap();
-    let cipher1 = common::create_default_cipher(&mut client).await.unwrap();
-    let cipher2 = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let cipher1 = common::create_default_cipher(&client).await.unwrap();
+    let cipher2 = common::create_default_cipher(&client).await.unwrap();
     client
         .send(
             &cipher::BulkDelete::builder()
@@ -119,9 +123,9 @@ async fn cipher_bulk_soft_delete() {
 
 #[tokio::test]
 async fn cipher_bulk_hard_delete() {
-    let mut client = common::login().await.unwrap();
-    let cipher1 = common::create_default_cipher(&mut client).await.unwrap();
-    let cipher2 = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let cipher1 = common::create_default_cipher(&client).await.unwrap();
+    let cipher2 = common::create_default_cipher(&client).await.unwrap();
     client
         .send(
             &cipher::BulkDelete::builder()
@@ -140,9 +144,9 @@ async fn cipher_bulk_hard_delete() {
 
 #[tokio::test]
 async fn cipher_modify_complete() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
-    let folder = common::create_default_folder(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
+    let folder = common::create_default_folder(&client).await.unwrap();
     let symmetric_key = client.symmetric_key().unwrap();
     let name = SymmetricEncryptedString::encrypt("foo2", &symmetric_key);
     let ty = created_cipher.ty;
@@ -151,6 +155,7 @@ async fn cipher_modify_complete() {
         ty,
         name: Some(SymmetricEncryptedString::encrypt(name, &symmetric_key)),
         value: Some(SymmetricEncryptedString::encrypt(value, &symmetric_key)),
+        linked_id: None,
     };
     let fields = vec![
         create_field(FieldType::Text, "field1", "value1"),
@@ -173,15 +178,15 @@ async fn cipher_modify_complete() {
     assert_eq!(cipher.notes, Some(notes));
     assert_eq!(cipher.fields, fields);
     assert_eq!(cipher.folder_id, Some(folder.id));
-    assert_eq!(cipher.favorite, true);
+    assert!(cipher.favorite);
 }
 
 #[tokio::test]
 #[cfg_attr(feature = "disable_vaultwarden_incompatible_tests", ignore)]
 async fn cipher_modify_partial() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
-    let folder = common::create_default_folder(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
+    let folder = common::create_default_folder(&client).await.unwrap();
     client
         .send(
             &cipher::ModifyPartial::builder()
@@ -199,15 +204,15 @@ async fn cipher_modify_partial() {
         .await
         .unwrap();
     assert_eq!(cipher.folder_id, Some(folder.id));
-    assert_eq!(cipher.favorite, true);
+    assert!(cipher.favorite);
 }
 
 // TODO: Test modify collections
 
 #[tokio::test]
 async fn cipher_restore() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
     client
         .send(&cipher::Delete {
             id: created_cipher.id,
@@ -226,9 +231,9 @@ async fn cipher_restore() {
 
 #[tokio::test]
 async fn cipher_bulk_restore() {
-    let mut client = common::login().await.unwrap();
-    let cipher1 = common::create_default_cipher(&mut client).await.unwrap();
-    let cipher2 = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let cipher1 = common::create_default_cipher(&client).await.unwrap();
+    let cipher2 = common::create_default_cipher(&client).await.unwrap();
     client
         .send(
             &cipher::BulkDelete::builder()
@@ -253,12 +258,12 @@ async fn cipher_bulk_restore() {
 
 #[tokio::test]
 async fn cipher_bulk_move() {
-    let mut client = common::login().await.unwrap();
-    let cipher1 = common::create_default_cipher(&mut client).await.unwrap();
-    let cipher2 = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let cipher1 = common::create_default_cipher(&client).await.unwrap();
+    let cipher2 = common::create_default_cipher(&client).await.unwrap();
 
     // Create folder and move ciphers into it
-    let folder = common::create_default_folder(&mut client).await.unwrap();
+    let folder = common::create_default_folder(&client).await.unwrap();
     client
         .send(&cipher::BulkMove {
             ids: vec![cipher1.id, cipher2.id],
@@ -288,18 +293,12 @@ async fn cipher_bulk_move() {
 #[tokio::test]
 #[ignore] // This test interferes with some other tests
 async fn cipher_purge() {
-    let mut client = common::login().await.unwrap();
-    let cipher1 = common::create_default_cipher(&mut client).await.unwrap();
-    let cipher2 = common::create_default_cipher(&mut client).await.unwrap();
-    // TODO: KDF type and iterations should not be hardcoded here
-    let source_key = SourceKey::new(
-        common::EMAIL,
-        common::PASSWORD,
-        KdfType::Pbkdf2Sha256,
-        100_000,
-    );
+    let client = common::login().await.unwrap();
+    let cipher1 = common::create_default_cipher(&client).await.unwrap();
+    let cipher2 = common::create_default_cipher(&client).await.unwrap();
+    let source_key = SourceKey::new(common::EMAIL, common::PASSWORD, &client.kdf());
     let master_password_hash =
-        MasterPasswordHash::new(&source_key, common::PASSWORD, KdfType::Pbkdf2Sha256);
+        MasterPasswordHash::new(&source_key, common::PASSWORD, client.kdf().kdf_type());
     client
         .send(
             &cipher::Purge::builder()
@@ -317,8 +316,8 @@ async fn cipher_purge() {
 
 #[tokio::test]
 async fn cipher_get_details() {
-    let mut client = common::login().await.unwrap();
-    let created_cipher = common::create_default_cipher(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
     let retrieved_cipher = client
         .send(&cipher::GetDetails {
             id: created_cipher.id,
@@ -330,10 +329,118 @@ async fn cipher_get_details() {
 
 #[tokio::test]
 async fn cipher_get_all_details() {
-    let mut client = common::login().await.unwrap();
+    let client = common::login().await.unwrap();
     let _ciphers: Vec<CipherDetails> = client
         .send(&cipher::GetAllDetails)
         .try_concat()
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn cipher_view() {
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
+    let key_ring = client.key_ring(&[]).unwrap();
+    let mut view = created_cipher.decrypt_with(&key_ring).unwrap();
+    assert_eq!(view.name, "foo");
+    let login = match &mut view.ty {
+        TypeView::Login(v) => v,
+        _ => panic!("cipher is not a login"),
+    };
+    assert_eq!(login.username.as_deref(), Some("bar"));
+    login.password = Some("baz".to_owned());
+    login.uris.push(LoginUriView::new(
+        "https://example.com",
+        LoginUriMatchType::Host,
+    ));
+    view.fields.push(FieldView {
+        ty: FieldType::Hidden,
+        name: Some("name".to_owned()),
+        value: Some("value".to_owned()),
+        linked_id: None,
+    });
+
+    let cipher = client
+        .send(&cipher::Modify::inherit(view.encrypt(key_ring.user_key())).build())
+        .await
+        .unwrap();
+    let modified_view = cipher.decrypt_with(&key_ring).unwrap();
+    assert_eq!(modified_view.ty, view.ty);
+    assert_eq!(modified_view.fields, view.fields);
+
+    let error = cipher.decrypt(&SymmetricKey::generate()).unwrap_err();
+    assert_eq!(error.field, "name");
+}
+
+#[tokio::test]
+async fn cipher_attachment() {
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
+    let key_ring = client.key_ring(&[]).unwrap();
+    let data = b"-----BEGIN CERTIFICATE-----".to_vec();
+    let cipher = client
+        .create_attachment(&created_cipher, "cert.pem", &data, &key_ring)
+        .await
+        .unwrap();
+    assert_eq!(cipher.attachments.len(), 1);
+    let attachment = &cipher.attachments[0];
+    assert!(attachment.key.is_some());
+    let view = cipher.decrypt_with(&key_ring).unwrap();
+    assert_eq!(view.attachments[0].file_name, "cert.pem");
+
+    let attachment = client
+        .send(&cipher::GetAttachment {
+            cipher_id: cipher.id,
+            attachment_id: attachment.id,
+        })
+        .await
+        .unwrap();
+    let downloaded_data = client
+        .download_attachment(&cipher, &attachment, &key_ring)
+        .await
+        .unwrap();
+    assert_eq!(downloaded_data, data);
+
+    client
+        .send(&cipher::DeleteAttachment {
+            cipher_id: cipher.id,
+            attachment_id: attachment.id,
+        })
+        .await
+        .unwrap();
+    let cipher = client.send(&cipher::Get { id: cipher.id }).await.unwrap();
+    assert!(cipher.attachments.is_empty());
+}
+
+#[tokio::test]
+async fn cipher_attachment_stream() {
+    let client = common::login().await.unwrap();
+    let created_cipher = common::create_default_cipher(&client).await.unwrap();
+    let key_ring = client.key_ring(&[]).unwrap();
+    let data = vec![7; 100_000];
+    let cipher = client
+        .create_attachment_from_reader(
+            &created_cipher,
+            "data.bin",
+            &mut data.as_slice(),
+            Cursor::new(Vec::new()),
+            &key_ring,
+        )
+        .await
+        .unwrap();
+    let attachment = &cipher.attachments[0];
+    let mut downloaded_data = Vec::new();
+    let length = client
+        .download_attachment_to_writer(
+            &cipher,
+            attachment,
+            &key_ring,
+            &mut Cursor::new(Vec::new()),
+            &mut downloaded_data,
+        )
+        .await
+        .unwrap();
+    assert_eq!(length, data.len() as u64);
+    assert_eq!(downloaded_data, data);
+}
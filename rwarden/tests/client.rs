@@ -0,0 +1,64 @@
+mod common;
+
+use rwarden::{account, KeyError, KeyPairError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{sync::Arc, time::Duration};
+
+#[tokio::test]
+async fn client_lock_and_unlock() {
+    let client = common::login().await.unwrap();
+    assert!(!client.is_locked());
+    client.lock();
+    assert!(client.is_locked());
+    assert!(matches!(client.symmetric_key(), Err(KeyError::Locked)));
+    client.send(&account::Get).await.unwrap();
+    assert!(client.unlock("wrong password").await.is_err());
+    assert!(client.is_locked());
+    client.unlock(common::PASSWORD).await.unwrap();
+    assert!(!client.is_locked());
+    client.symmetric_key().unwrap();
+}
+
+#[tokio::test]
+async fn client_lock_timeout() {
+    let client = common::login().await.unwrap();
+    let notified = Arc::new(AtomicBool::new(false));
+    let notified_clone = Arc::clone(&notified);
+    client.set_session_observer(move |session| {
+        notified_clone.store(session.source_key.is_none(), Ordering::SeqCst)
+    });
+    client
+        .clone()
+        .set_lock_timeout(Some(Duration::from_millis(100)));
+    assert_eq!(client.lock_timeout(), Some(Duration::from_millis(100)));
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.is_locked());
+    client.send(&account::Get).await.unwrap();
+    assert!(notified.load(Ordering::SeqCst));
+    client.unlock(common::PASSWORD).await.unwrap();
+    assert!(!client.is_locked());
+}
+
+#[tokio::test]
+async fn client_send_concurrently() {
+    let client = common::login().await.unwrap();
+    let handles = (0..4)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.send(&account::Get).await })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn client_generate_key_pair_already_exists() {
+    let client = common::login().await.unwrap();
+    client.private_key().unwrap();
+    assert!(matches!(
+        client.generate_key_pair().await,
+        Err(KeyPairError::AlreadyExists)
+    ));
+}
@@ -1,10 +1,12 @@
 #![allow(dead_code)] // https://github.com/rust-lang/rust/issues/46379
 
+use chrono::{Duration, Utc};
 use rwarden::cipher::{self, Cipher};
-use rwarden::crypto::{SymmetricEncryptedString, SymmetricKeyError};
+use rwarden::crypto::{SendKey, SymmetricEncryptedString};
 use rwarden::folder::{self, Folder};
+use rwarden::send::{self, Send};
 use rwarden::{
-    cache::EmptyCache, AnonymousClient, Client, DeviceType, LoginData, LoginError, Urls,
+    cache::EmptyCache, AnonymousClient, Client, DeviceType, KeyError, LoginData, LoginError, Urls,
 };
 use std::convert::Infallible;
 use thiserror::Error as ThisError;
@@ -18,7 +20,7 @@ pub const PASSWORD: &str = env!("RWARDEN_PASSWORD");
 #[derive(Debug, ThisError)]
 #[error(transparent)]
 pub enum Error {
-    SymmetricKey(#[from] SymmetricKeyError),
+    Key(#[from] KeyError),
     Other(#[from] rwarden::Error<Infallible>),
 }
 
@@ -41,7 +43,7 @@ pub async fn login() -> Result<Client<EmptyCache>, LoginError> {
     Ok(response.client)
 }
 
-pub async fn create_default_cipher(client: &mut Client<EmptyCache>) -> Result<Cipher, Error> {
+pub async fn create_default_cipher(client: &Client<EmptyCache>) -> Result<Cipher, Error> {
     let symmetric_key = client.symmetric_key()?;
     let name = SymmetricEncryptedString::encrypt("foo", &symmetric_key);
     let request_model = cipher::RequestModel::new(
@@ -59,7 +61,24 @@ pub async fn create_default_cipher(client: &mut Client<EmptyCache>) -> Result<Ci
         .await?)
 }
 
-pub async fn create_default_folder(client: &mut Client<EmptyCache>) -> Result<Folder, Error> {
+pub async fn create_default_send(
+    client: &Client<EmptyCache>,
+    send_key: &SendKey,
+) -> Result<Send, Error> {
+    let key = send_key.stretch();
+    let request_model = send::RequestModel::new(
+        SymmetricEncryptedString::encrypt("foo", &key),
+        send::RequestType::Text(send::Text {
+            text: Some(SymmetricEncryptedString::encrypt("bar", &key)),
+            hidden: false,
+        }),
+        send_key.protect(&client.symmetric_key()?),
+        (Utc::now() + Duration::days(1)).into(),
+    );
+    Ok(client.send(&send::Create { request_model }).await?)
+}
+
+pub async fn create_default_folder(client: &Client<EmptyCache>) -> Result<Folder, Error> {
     let folder_name = SymmetricEncryptedString::encrypt("foo", &client.symmetric_key()?);
     Ok(client.send(&folder::Create { name: folder_name }).await?)
 }
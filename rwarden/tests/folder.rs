@@ -6,14 +6,14 @@ mod common;
 
 #[tokio::test]
 async fn folder_create() {
-    let mut client = common::login().await.unwrap();
-    common::create_default_folder(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    common::create_default_folder(&client).await.unwrap();
 }
 
 #[tokio::test]
 async fn folder_get() {
-    let mut client = common::login().await.unwrap();
-    let created_folder = common::create_default_folder(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_folder = common::create_default_folder(&client).await.unwrap();
     let retrieved_folder = client
         .send(&folder::Get {
             id: created_folder.id,
@@ -25,14 +25,14 @@ async fn folder_get() {
 
 #[tokio::test]
 async fn folder_get_all() {
-    let mut client = common::login().await.unwrap();
+    let client = common::login().await.unwrap();
     let _folders: Vec<Folder> = client.send(&folder::GetAll).try_concat().await.unwrap();
 }
 
 #[tokio::test]
 async fn folder_delete() {
-    let mut client = common::login().await.unwrap();
-    let created_folder = common::create_default_folder(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let created_folder = common::create_default_folder(&client).await.unwrap();
     client
         .send(&folder::Delete {
             id: created_folder.id,
@@ -50,8 +50,8 @@ async fn folder_delete() {
 
 #[tokio::test]
 async fn folder_modify() {
-    let mut client = common::login().await.unwrap();
-    let folder = common::create_default_folder(&mut client).await.unwrap();
+    let client = common::login().await.unwrap();
+    let folder = common::create_default_folder(&client).await.unwrap();
     let symmetric_key = client.symmetric_key().unwrap();
     let name = SymmetricEncryptedString::encrypt("foo2", &symmetric_key);
     let folder = client
@@ -0,0 +1,146 @@
+mod common;
+
+use futures_util::TryStreamExt;
+use rwarden::crypto::{MasterPasswordHash, SourceKey};
+use rwarden::organization::{self, User, UserStatus, UserType};
+use rwarden::{account, collection};
+
+#[tokio::test]
+async fn organization_create_and_delete() {
+    let client = common::login().await.unwrap();
+    let created_organization = client
+        .create_organization("Organization", common::EMAIL, "Collection")
+        .await
+        .unwrap();
+    assert!(created_organization.has_public_and_private_keys);
+    let retrieved_organization = client
+        .send(&organization::Get {
+            id: created_organization.id,
+        })
+        .await
+        .unwrap();
+    assert_eq!(created_organization.name, retrieved_organization.name);
+    let _keys = client
+        .send(&organization::GetKeys {
+            id: created_organization.id,
+        })
+        .await
+        .unwrap();
+
+    let source_key = SourceKey::new(common::EMAIL, common::PASSWORD, &client.kdf());
+    let master_password_hash =
+        MasterPasswordHash::new(&source_key, common::PASSWORD, client.kdf().kdf_type());
+    client
+        .send(&organization::Delete {
+            id: created_organization.id,
+            master_password_hash,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn organization_modify() {
+    let client = common::login().await.unwrap();
+    let created_organization = client
+        .create_organization("Organization", common::EMAIL, "Collection")
+        .await
+        .unwrap();
+    let modified_organization = client
+        .send(
+            &organization::Modify::builder()
+                .id(created_organization.id)
+                .name("Modified organization")
+                .billing_email(common::EMAIL)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(modified_organization.name, "Modified organization");
+}
+
+#[tokio::test]
+async fn organization_users() {
+    let client = common::login().await.unwrap();
+    let created_organization = client
+        .create_organization("Organization", common::EMAIL, "Collection")
+        .await
+        .unwrap();
+    let users: Vec<User> = client
+        .send(&organization::GetAllUsers {
+            organization_id: created_organization.id,
+        })
+        .try_concat()
+        .await
+        .unwrap();
+    let owner = users.iter().find(|v| v.email == common::EMAIL).unwrap();
+    assert_eq!(owner.user_type, UserType::Owner);
+    assert_eq!(owner.status, UserStatus::Confirmed);
+    let user = client
+        .send(&organization::GetUser {
+            organization_id: created_organization.id,
+            user_id: owner.id,
+        })
+        .await
+        .unwrap();
+    assert_eq!(user.user_type, UserType::Owner);
+
+    client
+        .send(
+            &organization::InviteUsers::builder()
+                .organization_id(created_organization.id)
+                .emails(vec!["invited@example.com".to_owned()])
+                .user_type(UserType::User)
+                .access_all(true)
+                .build(),
+        )
+        .await
+        .unwrap();
+    let users: Vec<User> = client
+        .send(&organization::GetAllUsers {
+            organization_id: created_organization.id,
+        })
+        .try_concat()
+        .await
+        .unwrap();
+    let invited_user = users
+        .iter()
+        .find(|v| v.email == "invited@example.com")
+        .unwrap();
+    assert_eq!(invited_user.status, UserStatus::Invited);
+    assert!(client
+        .confirm_organization_user(created_organization.id, invited_user)
+        .await
+        .is_err());
+    client
+        .send(&organization::RemoveUser {
+            organization_id: created_organization.id,
+            user_id: invited_user.id,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn organization_key_ring() {
+    let client = common::login().await.unwrap();
+    let created_organization = client
+        .create_organization("Organization", common::EMAIL, "Collection")
+        .await
+        .unwrap();
+    let account = client.send(&account::Get).await.unwrap();
+    let key_ring = client.key_ring(&account.organizations).unwrap();
+    assert!(key_ring.organization_key(created_organization.id).is_some());
+    let collections: Vec<collection::Collection> = client
+        .send(&collection::GetAll {
+            organization_id: created_organization.id,
+        })
+        .try_concat()
+        .await
+        .unwrap();
+    let collection = &collections[0];
+    assert_eq!(
+        collection.decrypt_with(&key_ring).unwrap().name,
+        "Collection"
+    );
+}
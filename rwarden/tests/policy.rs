@@ -0,0 +1,12 @@
+mod common;
+
+use rwarden::policy::EnforcedPolicies;
+use rwarden::sync;
+
+#[tokio::test]
+async fn policy_enforced_policies_from_sync() {
+    let client = common::login().await.unwrap();
+    let sync = client.send(&sync::Get).await.unwrap();
+    let policies = EnforcedPolicies::from_sync(&sync);
+    assert!(policies.iter().all(|v| v.enabled));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use futures_util::TryStreamExt;
+use rwarden::crypto::SendKey;
+use rwarden::send::{self, Send};
+
+#[tokio::test]
+async fn send_create() {
+    let client = common::login().await.unwrap();
+    common::create_default_send(&client, &SendKey::generate())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn send_get() {
+    let client = common::login().await.unwrap();
+    let created_send = common::create_default_send(&client, &SendKey::generate())
+        .await
+        .unwrap();
+    let retrieved_send = client
+        .send(&send::Get {
+            id: created_send.id,
+        })
+        .await
+        .unwrap();
+    assert_eq!(created_send.id, retrieved_send.id);
+    assert_eq!(created_send.name, retrieved_send.name);
+    assert_eq!(created_send.key, retrieved_send.key);
+}
+
+#[tokio::test]
+async fn send_get_all() {
+    let client = common::login().await.unwrap();
+    let _sends: Vec<Send> = client.send(&send::GetAll).try_concat().await.unwrap();
+}
+
+#[tokio::test]
+async fn send_delete() {
+    let client = common::login().await.unwrap();
+    let created_send = common::create_default_send(&client, &SendKey::generate())
+        .await
+        .unwrap();
+    client
+        .send(&send::Delete {
+            id: created_send.id,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn send_access_with_password() {
+    let client = common::login().await.unwrap();
+    let send_key = SendKey::generate();
+    let created_send = common::create_default_send(&client, &send_key)
+        .await
+        .unwrap();
+    assert_eq!(
+        created_send
+            .send_key(&client.symmetric_key().unwrap())
+            .unwrap(),
+        send_key
+    );
+    let password_hash = send_key.hash_password("password");
+    let modified_send = client
+        .send(
+            &send::Modify::builder()
+                .id(created_send.id)
+                .request_model(
+                    send::RequestModel::from(created_send).with_password(password_hash.clone()),
+                )
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(modified_send.password.is_some());
+
+    let anonymous_client = common::client();
+    assert!(anonymous_client
+        .access_send(&modified_send.access_id, None)
+        .await
+        .is_err());
+    let accessed_send = anonymous_client
+        .access_send(&modified_send.access_id, Some(&password_hash))
+        .await
+        .unwrap();
+    let key = SendKey::decode(send_key.encode()).unwrap().stretch();
+    assert_eq!(accessed_send.name.decrypt(&key).unwrap(), "foo");
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use rwarden::{account, cache::EmptyCache};
+
+#[tokio::test]
+async fn session_seal_and_open() {
+    let client = common::login().await.unwrap();
+    let session = client.session();
+    let symmetric_key = client.symmetric_key().unwrap();
+    let sealed = session.seal(&symmetric_key);
+    assert_eq!(sealed.open(&symmetric_key).unwrap(), session);
+    assert!(sealed.open_with_pin("1234").await.is_err());
+    let sealed = session.seal_with_pin("1234").await;
+    assert_eq!(sealed.open_with_pin("1234").await.unwrap(), session);
+    assert!(sealed.open_with_pin("4321").await.is_err());
+}
+
+#[tokio::test]
+async fn session_into_client() {
+    let client = common::login().await.unwrap();
+    let sealed = client.session().seal_with_pin("1234").await;
+    let json = serde_json::to_string(&sealed).unwrap();
+    let sealed = serde_json::from_str::<rwarden::SealedSession>(&json).unwrap();
+    let client = sealed
+        .open_with_pin("1234")
+        .await
+        .unwrap()
+        .into_client(EmptyCache);
+    client.send(&account::Get).await.unwrap();
+}
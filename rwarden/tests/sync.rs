@@ -4,6 +4,6 @@ use rwarden::sync::{self, Sync};
 
 #[tokio::test]
 async fn sync_get() {
-    let mut client = common::login().await.unwrap();
+    let client = common::login().await.unwrap();
     let _snyc: Sync = client.send(&sync::Get).await.unwrap();
 }
@@ -0,0 +1,27 @@
+mod common;
+
+use rwarden::crypto::{MasterPasswordHash, SourceKey};
+use rwarden::two_factor;
+
+#[tokio::test]
+async fn two_factor_get_all() {
+    let client = common::login().await.unwrap();
+    let _providers = client.send(&two_factor::GetAll).await.unwrap();
+}
+
+#[tokio::test]
+async fn two_factor_get_recovery_code() {
+    let client = common::login().await.unwrap();
+    let source_key = SourceKey::new(common::EMAIL, common::PASSWORD, &client.kdf());
+    let master_password_hash =
+        MasterPasswordHash::new(&source_key, common::PASSWORD, client.kdf().kdf_type());
+    let recovery_code = client
+        .send(
+            &two_factor::GetRecoveryCode::builder()
+                .master_password_hash(master_password_hash)
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(!recovery_code.code.is_empty());
+}
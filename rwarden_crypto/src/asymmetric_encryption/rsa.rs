@@ -1,5 +1,6 @@
-use crate::{Decrypt, Parse};
-use rsa::RsaPrivateKey;
+use crate::{Decrypt, Encrypt, Parse};
+use rand::rngs::OsRng;
+use rsa::{PublicKey, RsaPrivateKey, RsaPublicKey};
 use sha1::Sha1;
 use sha2::Sha256;
 use std::{fmt, num::ParseIntError};
@@ -47,6 +48,25 @@ impl Parse for Rsa2048OaepSha1 {
     }
 }
 
+impl Encrypt for Rsa2048OaepSha1 {
+    type Params = RsaPublicKey;
+
+    /// Encrypts the plaintext with the given public key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plaintext is too long for the public key (more than 214 bytes for a 2048 bit
+    /// key).
+    fn encrypt<P: AsRef<[u8]>>(plaintext: P, params: &Self::Params) -> Self {
+        let public_key = params;
+        let padding = rsa::PaddingScheme::new_oaep::<Sha1>();
+        let ciphertext = public_key
+            .encrypt(&mut OsRng, padding, plaintext.as_ref())
+            .expect("plaintext is too long for the public key");
+        Self { ciphertext }
+    }
+}
+
 impl Decrypt for Rsa2048OaepSha1 {
     type Params = RsaPrivateKey;
     type Error = rsa::errors::Error;
@@ -93,6 +113,25 @@ impl Parse for Rsa2048OaepSha256 {
     }
 }
 
+impl Encrypt for Rsa2048OaepSha256 {
+    type Params = RsaPublicKey;
+
+    /// Encrypts the plaintext with the given public key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plaintext is too long for the public key (more than 190 bytes for a 2048 bit
+    /// key).
+    fn encrypt<P: AsRef<[u8]>>(plaintext: P, params: &Self::Params) -> Self {
+        let public_key = params;
+        let padding = rsa::PaddingScheme::new_oaep::<Sha256>();
+        let ciphertext = public_key
+            .encrypt(&mut OsRng, padding, plaintext.as_ref())
+            .expect("plaintext is too long for the public key");
+        Self { ciphertext }
+    }
+}
+
 impl Decrypt for Rsa2048OaepSha256 {
     type Params = RsaPrivateKey;
     type Error = rsa::errors::Error;
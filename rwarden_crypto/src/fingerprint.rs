@@ -0,0 +1,116 @@
+use hkdf::Hkdf;
+use rsa::pkcs8::ToPublicKey;
+use rsa::RsaPublicKey;
+use sha2::{Digest, Sha256};
+
+/// The fingerprint of a public key.
+///
+/// The fingerprint phrase is shown to users, so that they can verify the public key of another
+/// user out of band (e.g. before confirming a user of an organization).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint(pub [u8; 32]);
+
+impl Fingerprint {
+    const MINIMUM_ENTROPY: f64 = 64.0;
+
+    /// Creates the fingerprint of a public key.
+    ///
+    /// The fingerprint material is the ID of the user that owns the public key.
+    pub fn new<M: AsRef<[u8]>>(fingerprint_material: M, public_key: &RsaPublicKey) -> Self {
+        // `unwrap` is safe here because encoding a valid RSA public key does not fail
+        let public_key = public_key.to_public_key_der().unwrap();
+        let key_fingerprint = Sha256::digest(public_key.as_ref());
+        // `unwrap` is safe here because a SHA-256 hash is a valid PRK and 32 bytes is a valid
+        // output length for HKDF SHA-256
+        let hkdf = Hkdf::<Sha256>::from_prk(&key_fingerprint).unwrap();
+        let mut fingerprint = [0; 32];
+        hkdf.expand(fingerprint_material.as_ref(), &mut fingerprint)
+            .unwrap();
+        Self(fingerprint)
+    }
+
+    /// Returns the words of the fingerprint phrase.
+    ///
+    /// The word list must be the [EFF long word list] to get the same phrase as the official
+    /// clients, which join the words with `-`. The number of words is chosen so that the phrase
+    /// has at least 64 bits of entropy.
+    ///
+    /// [EFF long word list]: https://www.eff.org/dice
+    ///
+    /// # Panics
+    ///
+    /// Panics if the word list contains less than two words.
+    pub fn phrase<'a>(&self, word_list: &[&'a str]) -> Vec<&'a str> {
+        assert!(word_list.len() >= 2, "word list is too short");
+        let len = word_list.len() as u32;
+        let entropy_per_word = f64::from(len).ln() / 2f64.ln();
+        let word_count = (Self::MINIMUM_ENTROPY / entropy_per_word).ceil() as usize;
+        // The fingerprint is interpreted as a big-endian integer that is repeatedly divided by the
+        // length of the word list, the remainders are the indices of the words
+        let mut number = self.0;
+        let mut words = Vec::with_capacity(word_count);
+        for _ in 0..word_count {
+            let mut remainder = 0u64;
+            for byte in number.iter_mut() {
+                let value = (remainder << 8) | u64::from(*byte);
+                *byte = (value / u64::from(len)) as u8;
+                remainder = value % u64::from(len);
+            }
+            words.push(word_list[remainder as usize]);
+        }
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RsaKeyPair;
+    use rsa::pkcs8::FromPublicKey;
+
+    const PUBLIC_KEY: &str = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAvX1w1/LJhUEByeJ3it4I0CCK4Roe1QmJc21mvkokb2ZW21dePvNiGeMth2jExhWjsuCwHnmIY4qRCoZZZ3asR2tODDpserS1COLy2QhCG1U3/DOc+x1T9hLWxP/q7vFirqHgaKfp0vSf7HM+v2FJUFHzV5LKjEm/CO9MtceHDgWdcYi+ndiDrwf0p4FjCOf6DPZCzwqZzmwHh8wL+elXvn7p+92+SaB886lX/1RCy6cQk0iv7V4tzhx9RH6H0wWN5ByuRbANW9kMlyDBzn8rJcOyDS3AnxPEuPAcaYNuCSqh74qoE+8YR5HgodfiwrwseEccs39ui6DxyCNEJJJl+wIDAQAB";
+
+    #[test]
+    fn phrase() {
+        let word_list = [
+            "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p",
+        ];
+        let mut fingerprint = Fingerprint([0; 32]);
+        fingerprint.0[31] = 0x21;
+        let phrase = fingerprint.phrase(&word_list);
+        assert_eq!(phrase.len(), 16);
+        assert_eq!(&phrase[..3], ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn fingerprint_material() {
+        let key_pair = RsaKeyPair::generate();
+        let fingerprint = Fingerprint::new("user-id", &key_pair.public_key());
+        assert_eq!(
+            fingerprint,
+            Fingerprint::new("user-id", &key_pair.public_key())
+        );
+        assert_ne!(
+            fingerprint,
+            Fingerprint::new("other-user-id", &key_pair.public_key())
+        );
+    }
+
+    #[test]
+    fn known_answer() {
+        let public_key =
+            RsaPublicKey::from_public_key_der(&base64::decode(PUBLIC_KEY).unwrap()).unwrap();
+        let fingerprint = Fingerprint::new("a09726a0-9590-49d1-a5f5-afe300b6a515", &public_key);
+        assert_eq!(
+            base64::encode(fingerprint.0),
+            "kBAdm8HV14dIbhg6c9bZyKgHWlZc9PHgwfkXkyV/CHY="
+        );
+        // A word list with the length of the EFF long word list
+        let words = (0..7776).map(|i| i.to_string()).collect::<Vec<_>>();
+        let word_list = words.iter().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(
+            fingerprint.phrase(&word_list),
+            ["6102", "4455", "2086", "1884", "1850"]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::KdfType;
+use argon2::{Algorithm, Argon2, Params, Version};
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum KdfError {
+    #[error("the memory and parallelism parameters are required for Argon2id")]
+    MissingParameters,
+    #[error("invalid Argon2id parameters: {0}")]
+    InvalidArgon2Parameters(argon2::Error),
+}
+
+/// The KDF and its parameters that are used to derive the [`SourceKey`] from the master password.
+///
+/// [`SourceKey`]: crate::SourceKey
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "RawKdf", into = "RawKdf")]
+pub struct Kdf {
+    kdf_type: KdfType,
+    iterations: u32,
+    memory: Option<u32>,
+    parallelism: Option<u32>,
+}
+
+impl Kdf {
+    /// Creates a new PBKDF2 SHA-256 [`Kdf`].
+    pub fn pbkdf2_sha256(iterations: u32) -> Self {
+        Self {
+            kdf_type: KdfType::Pbkdf2Sha256,
+            iterations,
+            memory: None,
+            parallelism: None,
+        }
+    }
+
+    /// Creates a new Argon2id [`Kdf`].
+    ///
+    /// The memory is specified in MiB.
+    pub fn argon2id(iterations: u32, memory: u32, parallelism: u32) -> Result<Self, KdfError> {
+        argon2_params(iterations, memory, parallelism)?;
+        Ok(Self {
+            kdf_type: KdfType::Argon2id,
+            iterations,
+            memory: Some(memory),
+            parallelism: Some(parallelism),
+        })
+    }
+
+    /// Returns the KDF type.
+    pub fn kdf_type(&self) -> KdfType {
+        self.kdf_type
+    }
+
+    /// Returns the number of iterations.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Returns the memory in MiB.
+    ///
+    /// This is `None` if the KDF type is not [`KdfType::Argon2id`].
+    pub fn memory(&self) -> Option<u32> {
+        self.memory
+    }
+
+    /// Returns the degree of parallelism.
+    ///
+    /// This is `None` if the KDF type is not [`KdfType::Argon2id`].
+    pub fn parallelism(&self) -> Option<u32> {
+        self.parallelism
+    }
+
+    pub(crate) fn derive(&self, password: &[u8], salt: &[u8], output: &mut [u8; 32]) {
+        match self.kdf_type {
+            KdfType::Pbkdf2Sha256 => {
+                pbkdf2::<Hmac<Sha256>>(password, salt, self.iterations, output);
+            }
+            KdfType::Argon2id => {
+                // `unwrap` is safe here because the parameters are validated when the `Kdf` is
+                // created
+                let params = argon2_params(
+                    self.iterations,
+                    self.memory.unwrap(),
+                    self.parallelism.unwrap(),
+                )
+                .unwrap();
+                // `unwrap` is safe here because the salt is a SHA-256 hash and the password is
+                // shorter than 4 GiB
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password, salt, output)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+fn argon2_params(iterations: u32, memory: u32, parallelism: u32) -> Result<Params, KdfError> {
+    let memory = memory
+        .checked_mul(1024)
+        .ok_or(KdfError::InvalidArgon2Parameters(
+            argon2::Error::MemoryTooMuch,
+        ))?;
+    Params::new(memory, iterations, parallelism, Some(32))
+        .map_err(KdfError::InvalidArgon2Parameters)
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawKdf {
+    #[serde(rename = "Kdf")]
+    kdf_type: KdfType,
+    #[serde(rename = "KdfIterations")]
+    iterations: u32,
+    #[serde(rename = "KdfMemory", default)]
+    memory: Option<u32>,
+    #[serde(rename = "KdfParallelism", default)]
+    parallelism: Option<u32>,
+}
+
+impl TryFrom<RawKdf> for Kdf {
+    type Error = KdfError;
+
+    fn try_from(raw: RawKdf) -> Result<Self, Self::Error> {
+        match raw.kdf_type {
+            KdfType::Pbkdf2Sha256 => Ok(Self::pbkdf2_sha256(raw.iterations)),
+            KdfType::Argon2id => match (raw.memory, raw.parallelism) {
+                (Some(memory), Some(parallelism)) => {
+                    Self::argon2id(raw.iterations, memory, parallelism)
+                }
+                _ => Err(KdfError::MissingParameters),
+            },
+        }
+    }
+}
+
+impl From<Kdf> for RawKdf {
+    fn from(kdf: Kdf) -> Self {
+        Self {
+            kdf_type: kdf.kdf_type,
+            iterations: kdf.iterations,
+            memory: kdf.memory,
+            parallelism: kdf.parallelism,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argon2id_memory_in_mib() {
+        let params = argon2_params(3, 64, 4).unwrap();
+        assert_eq!(params.m_cost(), 64 * 1024);
+        assert_eq!(params.t_cost(), 3);
+        assert_eq!(params.p_cost(), 4);
+    }
+
+    #[test]
+    fn deserialize_prelogin_response() {
+        let kdf: Kdf = serde_json::from_str(
+            r#"{"Kdf":1,"KdfIterations":3,"KdfMemory":64,"KdfParallelism":4}"#,
+        )
+        .unwrap();
+        assert_eq!(kdf, Kdf::argon2id(3, 64, 4).unwrap());
+
+        let kdf: Kdf = serde_json::from_str(
+            r#"{"Kdf":0,"KdfIterations":100000,"KdfMemory":null,"KdfParallelism":null}"#,
+        )
+        .unwrap();
+        assert_eq!(kdf, Kdf::pbkdf2_sha256(100_000));
+    }
+
+    #[test]
+    fn argon2id_missing_parameters() {
+        let raw = RawKdf {
+            kdf_type: KdfType::Argon2id,
+            iterations: 3,
+            memory: Some(64),
+            parallelism: None,
+        };
+        assert!(matches!(
+            Kdf::try_from(raw),
+            Err(KdfError::MissingParameters)
+        ));
+        assert!(serde_json::from_str::<Kdf>(r#"{"Kdf":1,"KdfIterations":3}"#).is_err());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        for kdf in [
+            Kdf::pbkdf2_sha256(100_000),
+            Kdf::argon2id(3, 64, 4).unwrap(),
+        ] {
+            let json = serde_json::to_string(&kdf).unwrap();
+            assert_eq!(serde_json::from_str::<Kdf>(&json).unwrap(), kdf);
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use rand::{rngs::OsRng, RngCore};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_repr::{Deserialize_repr as DeserializeRepr, Serialize_repr as SerializeRepr};
-use std::{error::Error as StdError, fmt, marker::PhantomData, string::FromUtf8Error};
+use std::{error::Error as StdError, fmt, marker::PhantomData, str::Utf8Error};
+use zeroize::Zeroize;
 
 pub use asymmetric_encryption::AsymmetricEncryption;
+pub use fingerprint::Fingerprint;
+pub use kdf::{Kdf, KdfError};
 pub use master_password_hash::MasterPasswordHash;
+pub use rsa_key_pair::{RsaKeyPair, RsaKeyPairError};
+pub use send_key::{SendKey, SendKeyError};
 pub use source_key::SourceKey;
 pub use symmetric_encryption::SymmetricEncryption;
 pub use symmetric_key::{SymmetricKey, SymmetricKeyError};
@@ -12,7 +17,11 @@ pub use symmetric_key::{SymmetricKey, SymmetricKeyError};
 pub mod asymmetric_encryption;
 pub mod symmetric_encryption;
 
+mod fingerprint;
+mod kdf;
 mod master_password_hash;
+mod rsa_key_pair;
+mod send_key;
 mod source_key;
 mod symmetric_key;
 
@@ -38,6 +47,22 @@ pub trait Parse: Sized {
 pub enum KdfType {
     /// PBKDF2 SHA-256.
     Pbkdf2Sha256 = 0,
+    /// Argon2id.
+    Argon2id = 1,
+}
+
+/// Runs a blocking function on tokio's blocking thread pool.
+#[cfg(feature = "tokio")]
+pub(crate) async fn spawn_blocking<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(v) => v,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => panic!("blocking task failed: {}", e),
+    }
 }
 
 pub(crate) fn generate_iv() -> [u8; 16] {
@@ -50,14 +75,7 @@ pub(crate) fn generate_iv() -> [u8; 16] {
 pub fn generate_protected_symmetric_key(
     source_key: &SourceKey,
 ) -> GenericEncryptedBytes<symmetric_encryption::AesCbc256HmacSha256> {
-    let (enc, mac) = source_key.expand();
-    let keys = SymmetricKey::generate();
-    // unwrap is safe here because `SymmetricKey::generate()` always sets the mac field to `Some`
-    let data = [keys.enc, keys.mac.unwrap()].concat();
-    GenericEncryptedBytes(symmetric_encryption::AesCbc256HmacSha256::encrypt(
-        data,
-        &(enc, mac),
-    ))
+    SymmetricKey::generate().protect(source_key)
 }
 
 pub type SymmetricEncryptedBytes = GenericEncryptedBytes<SymmetricEncryption>;
@@ -144,7 +162,7 @@ impl<E: fmt::Display> Serialize for GenericEncryptedBytes<E> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StringDecryptionError<E> {
-    InvalidUtf8(FromUtf8Error),
+    InvalidUtf8(Utf8Error),
     Other(E),
 }
 
@@ -159,8 +177,8 @@ impl<E: StdError> fmt::Display for StringDecryptionError<E> {
     }
 }
 
-impl<E> From<FromUtf8Error> for StringDecryptionError<E> {
-    fn from(e: FromUtf8Error) -> Self {
+impl<E> From<Utf8Error> for StringDecryptionError<E> {
+    fn from(e: Utf8Error) -> Self {
         Self::InvalidUtf8(e)
     }
 }
@@ -189,7 +207,15 @@ impl<E: Decrypt> GenericEncryptedString<E> {
             .0
             .decrypt(params)
             .map_err(StringDecryptionError::Other)?;
-        Ok(String::from_utf8(bytes)?)
+        match String::from_utf8(bytes) {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                // Make sure that the decrypted bytes are not kept in the error
+                let utf8_error = e.utf8_error();
+                e.into_bytes().zeroize();
+                Err(utf8_error.into())
+            }
+        }
     }
 }
 
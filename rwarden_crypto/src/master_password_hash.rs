@@ -4,9 +4,12 @@ use pbkdf2::pbkdf2;
 use serde::{Serialize, Serializer};
 use sha2::Sha256;
 use std::fmt;
+use zeroize::Zeroize;
 
 /// A hashed master password.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The hash is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
 pub struct MasterPasswordHash(pub [u8; 32]);
 
 impl MasterPasswordHash {
@@ -16,7 +19,7 @@ impl MasterPasswordHash {
         P: AsRef<[u8]>,
     {
         match kdf_type {
-            KdfType::Pbkdf2Sha256 => {
+            KdfType::Pbkdf2Sha256 | KdfType::Argon2id => {
                 let mut master_password_hash = [0; 32];
                 pbkdf2::<Hmac<Sha256>>(
                     &source_key.0,
@@ -29,12 +32,37 @@ impl MasterPasswordHash {
         }
     }
 
+    /// Creates a new [`MasterPasswordHash`] on tokio's blocking thread pool.
+    ///
+    /// This requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub async fn derive<P>(source_key: &SourceKey, password: P, kdf_type: KdfType) -> Self
+    where
+        P: AsRef<[u8]>,
+    {
+        let source_key = source_key.clone();
+        let password = zeroize::Zeroizing::new(password.as_ref().to_vec());
+        crate::spawn_blocking(move || Self::new(&source_key, &*password, kdf_type)).await
+    }
+
     /// Encodes the master password hash as base64.
     pub fn encode(&self) -> String {
         base64::encode(self.0)
     }
 }
 
+impl Drop for MasterPasswordHash {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for MasterPasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MasterPasswordHash(<redacted>)")
+    }
+}
+
 impl fmt::Display for MasterPasswordHash {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.encode())
@@ -0,0 +1,141 @@
+use crate::symmetric_encryption::DecryptionError;
+use crate::{SymmetricEncryptedBytes, SymmetricKey};
+use rand::rngs::OsRng;
+use rsa::pkcs8::{FromPrivateKey, ToPrivateKey, ToPublicKey};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use std::fmt;
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+#[derive(Debug, Error)]
+pub enum RsaKeyPairError {
+    #[error("decryption error")]
+    Decryption(#[from] DecryptionError),
+    #[error("failed to parse private key")]
+    Parse(#[from] pkcs8::Error),
+}
+
+/// An RSA-2048 key pair.
+///
+/// The private key is encrypted with the symmetric key of its owner and the public key is stored
+/// unencrypted on the server, so that other users can encrypt data (e.g. the symmetric key of an
+/// organization) for the owner.
+///
+/// The private key is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RsaKeyPair {
+    private_key: RsaPrivateKey,
+}
+
+impl RsaKeyPair {
+    const BITS: usize = 2048;
+
+    /// Decrypts a protected private key with the given symmetric key.
+    pub fn new(
+        symmetric_key: &SymmetricKey,
+        protected_private_key: &SymmetricEncryptedBytes,
+    ) -> Result<Self, RsaKeyPairError> {
+        let mut bytes = protected_private_key.decrypt(symmetric_key)?;
+        let private_key = RsaPrivateKey::from_pkcs8_der(&bytes);
+        bytes.zeroize();
+        Ok(Self {
+            private_key: private_key?,
+        })
+    }
+
+    /// Generates a new random key pair.
+    ///
+    /// Generating the key pair can take several hundred milliseconds, so [`RsaKeyPair::generate_async`]
+    /// should be preferred in asynchronous code.
+    pub fn generate() -> Self {
+        // `unwrap` is safe here because generating a 2048 bit key does not fail
+        let private_key = RsaPrivateKey::new(&mut OsRng, Self::BITS).unwrap();
+        Self { private_key }
+    }
+
+    /// Generates a new random key pair on tokio's blocking thread pool.
+    ///
+    /// This requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub async fn generate_async() -> Self {
+        crate::spawn_blocking(Self::generate).await
+    }
+
+    /// Returns the private key.
+    pub fn private_key(&self) -> &RsaPrivateKey {
+        &self.private_key
+    }
+
+    /// Returns the public key.
+    pub fn public_key(&self) -> RsaPublicKey {
+        self.private_key.to_public_key()
+    }
+
+    /// Exports the public key as DER encoded SPKI.
+    pub fn public_key_der(&self) -> Vec<u8> {
+        // `unwrap` is safe here because encoding a valid RSA public key does not fail
+        let document = self.public_key().to_public_key_der().unwrap();
+        document.as_ref().to_vec()
+    }
+
+    /// Exports the private key as DER encoded PKCS#8.
+    pub fn private_key_der(&self) -> Zeroizing<Vec<u8>> {
+        // `unwrap` is safe here because encoding a valid RSA private key does not fail
+        let document = self.private_key.to_pkcs8_der().unwrap();
+        Zeroizing::new(document.as_ref().to_vec())
+    }
+
+    /// Encodes the public key as base64.
+    ///
+    /// This is the value that is sent to the server.
+    pub fn encode_public_key(&self) -> String {
+        base64::encode(self.public_key_der())
+    }
+
+    /// Encrypts the private key with the given symmetric key.
+    ///
+    /// The result is the protected private key that is stored on the server.
+    pub fn protect(&self, symmetric_key: &SymmetricKey) -> SymmetricEncryptedBytes {
+        SymmetricEncryptedBytes::encrypt(&*self.private_key_der(), symmetric_key)
+    }
+}
+
+impl From<RsaPrivateKey> for RsaKeyPair {
+    fn from(private_key: RsaPrivateKey) -> Self {
+        Self { private_key }
+    }
+}
+
+impl fmt::Debug for RsaKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RsaKeyPair(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asymmetric_encryption::{Rsa2048OaepSha1, Rsa2048OaepSha256};
+    use crate::GenericEncryptedBytes;
+
+    #[test]
+    fn protect_and_encrypt() {
+        let symmetric_key = SymmetricKey::generate();
+        let key_pair = RsaKeyPair::generate();
+        let protected_private_key = key_pair.protect(&symmetric_key);
+        let decrypted_key_pair = RsaKeyPair::new(&symmetric_key, &protected_private_key).unwrap();
+        assert_eq!(key_pair, decrypted_key_pair);
+
+        let public_key = key_pair.public_key();
+        let encrypted =
+            GenericEncryptedBytes::<Rsa2048OaepSha1>::encrypt(b"plaintext", &public_key)
+                .into_asymmetric();
+        let decrypted = encrypted.decrypt(key_pair.private_key()).unwrap();
+        assert_eq!(decrypted, b"plaintext");
+        let encrypted =
+            GenericEncryptedBytes::<Rsa2048OaepSha256>::encrypt(b"plaintext", &public_key)
+                .into_asymmetric();
+        let decrypted = encrypted.decrypt(key_pair.private_key()).unwrap();
+        assert_eq!(decrypted, b"plaintext");
+    }
+}
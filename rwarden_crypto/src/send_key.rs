@@ -0,0 +1,113 @@
+use crate::symmetric_encryption::DecryptionError;
+use crate::{SymmetricEncryptedBytes, SymmetricKey};
+use hkdf::Hkdf;
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use std::{convert::TryInto, fmt};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+#[derive(Debug, Clone, Error)]
+pub enum SendKeyError {
+    #[error("invalid length")]
+    InvalidLength,
+    #[error("invalid base64: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    #[error("decryption error")]
+    Decryption(#[from] DecryptionError),
+}
+
+/// The key material of a send.
+///
+/// The key that is used for encrypting the data of the send is derived from the key material with
+/// HKDF. The key material itself is encrypted with the symmetric key of the user.
+///
+/// The key material is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SendKey(pub [u8; 16]);
+
+impl SendKey {
+    const PASSWORD_ITERATIONS: u32 = 100_000;
+
+    /// Decrypts the key material of a send with the symmetric key of the user.
+    pub fn new(
+        symmetric_key: &SymmetricKey,
+        protected_send_key: &SymmetricEncryptedBytes,
+    ) -> Result<Self, SendKeyError> {
+        let mut bytes = protected_send_key.decrypt(symmetric_key)?;
+        let key = bytes.as_slice().try_into().map(Self);
+        bytes.zeroize();
+        key.map_err(|_| SendKeyError::InvalidLength)
+    }
+
+    /// Generates new random key material.
+    pub fn generate() -> Self {
+        let mut key = [0; 16];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Encrypts the key material with the symmetric key of the user.
+    pub fn protect(&self, symmetric_key: &SymmetricKey) -> SymmetricEncryptedBytes {
+        SymmetricEncryptedBytes::encrypt(self.0, symmetric_key)
+    }
+
+    /// Derives the key that is used for encrypting the data of the send with HKDF.
+    pub fn stretch(&self) -> SymmetricKey {
+        let hkdf = Hkdf::<Sha256>::new(Some(b"bitwarden-send"), &self.0);
+        let mut keys = [0; 64];
+        // `unwrap` is safe here because 64 bytes is a valid output length for HKDF SHA-256
+        hkdf.expand(b"send", &mut keys).unwrap();
+        let key = SymmetricKey {
+            enc: keys[0..32].try_into().unwrap(),
+            mac: Some(keys[32..64].try_into().unwrap()),
+        };
+        keys.zeroize();
+        key
+    }
+
+    /// Hashes the password of the send.
+    ///
+    /// The hash is base64 encoded and is the value that is sent to the server.
+    pub fn hash_password<P: AsRef<[u8]>>(&self, password: P) -> String {
+        let mut hash = [0; 32];
+        pbkdf2::<Hmac<Sha256>>(
+            password.as_ref(),
+            &self.0,
+            Self::PASSWORD_ITERATIONS,
+            &mut hash,
+        );
+        let value = base64::encode(hash);
+        hash.zeroize();
+        value
+    }
+
+    /// Encodes the key material as URL-safe base64 without padding.
+    ///
+    /// This is the encoding that is used in the fragment of the URL of a send.
+    pub fn encode(&self) -> String {
+        base64::encode_config(self.0, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Decodes key material that is encoded as URL-safe base64 without padding.
+    pub fn decode<S: AsRef<str>>(value: S) -> Result<Self, SendKeyError> {
+        let mut bytes = base64::decode_config(value.as_ref(), base64::URL_SAFE_NO_PAD)?;
+        let key = bytes.as_slice().try_into().map(Self);
+        bytes.zeroize();
+        key.map_err(|_| SendKeyError::InvalidLength)
+    }
+}
+
+impl Drop for SendKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SendKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SendKey(<redacted>)")
+    }
+}
@@ -1,35 +1,70 @@
-use crate::KdfType;
+use crate::{Kdf, KdfType, SymmetricKey};
 use hkdf::Hkdf;
-use hmac::Hmac;
-use pbkdf2::pbkdf2;
-use sha2::Sha256;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::{convert::TryInto, fmt};
+use zeroize::Zeroize;
 
 /// An intermediate type used for creating a [`SymmetricKey`] and [`MasterPasswordHash`].
 ///
 /// [`SymmetricKey`]: crate::SymmetricKey
 /// [`MasterPasswordHash`]: crate::MasterPasswordHash
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The key is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
 pub struct SourceKey(pub [u8; 32]);
 
 impl SourceKey {
     /// Creates a new [`SourceKey`].
-    pub fn new<E, P>(email: E, password: P, kdf_type: KdfType, kdf_iterations: u32) -> Self
+    ///
+    /// The email address is used as the salt. For Argon2id the SHA-256 hash of the email address
+    /// is used instead.
+    pub fn new<E, P>(email: E, password: P, kdf: &Kdf) -> Self
     where
         E: AsRef<[u8]>,
         P: AsRef<[u8]>,
     {
-        match kdf_type {
-            KdfType::Pbkdf2Sha256 => {
-                let mut source_key = [0; 32];
-                pbkdf2::<Hmac<Sha256>>(
-                    password.as_ref(),
-                    email.as_ref(),
-                    kdf_iterations,
-                    &mut source_key,
-                );
-                Self(source_key)
+        let mut source_key = [0; 32];
+        match kdf.kdf_type() {
+            KdfType::Pbkdf2Sha256 => kdf.derive(password.as_ref(), email.as_ref(), &mut source_key),
+            KdfType::Argon2id => {
+                let salt = Sha256::digest(email.as_ref());
+                kdf.derive(password.as_ref(), &salt, &mut source_key);
             }
         }
+        Self(source_key)
+    }
+
+    /// Creates a new [`SourceKey`] on tokio's blocking thread pool.
+    ///
+    /// Deriving the source key can take several hundred milliseconds, so this should be preferred
+    /// over [`SourceKey::new`] in asynchronous code. This requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub async fn derive<E, P>(email: E, password: P, kdf: &Kdf) -> Self
+    where
+        E: AsRef<[u8]>,
+        P: AsRef<[u8]>,
+    {
+        let email = email.as_ref().to_vec();
+        let password = zeroize::Zeroizing::new(password.as_ref().to_vec());
+        let kdf = *kdf;
+        crate::spawn_blocking(move || Self::new(email, &*password, &kdf)).await
+    }
+
+    /// Stretches the source key into a [`SymmetricKey`] with HKDF.
+    ///
+    /// This is the key that is used for encrypting the protected symmetric key.
+    pub fn stretch(&self) -> SymmetricKey {
+        let (enc, mac) = self.expand();
+        SymmetricKey {
+            enc,
+            mac: Some(mac),
+        }
+    }
+
+    /// Encodes the source key as base64.
+    pub fn encode(&self) -> String {
+        base64::encode(self.0)
     }
 
     pub(crate) fn expand(&self) -> ([u8; 32], [u8; 32]) {
@@ -41,3 +76,79 @@ impl SourceKey {
         (enc, mac)
     }
 }
+
+impl Drop for SourceKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SourceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SourceKey(<redacted>)")
+    }
+}
+
+impl Serialize for SourceKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+struct SourceKeyVisitor;
+
+impl<'de> de::Visitor<'de> for SourceKeyVisitor {
+    type Value = SourceKey;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("base64 encoded source key")
+    }
+
+    fn visit_str<Error: de::Error>(self, value: &str) -> Result<Self::Value, Error> {
+        let mut bytes =
+            base64::decode(value).map_err(|e| Error::custom(format!("invalid base64: {}", e)))?;
+        let source_key = bytes.as_slice().try_into().map(SourceKey);
+        bytes.zeroize();
+        source_key.map_err(|_| Error::custom("invalid length"))
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(SourceKeyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argon2id_salt() {
+        let kdf = Kdf::argon2id(1, 8, 1).unwrap();
+        let mut expected = [0; 32];
+        kdf.derive(
+            b"password",
+            &Sha256::digest(b"user@example.com"),
+            &mut expected,
+        );
+        let source_key = SourceKey::new("user@example.com", "password", &kdf);
+        assert_eq!(source_key.0, expected);
+    }
+
+    #[test]
+    fn argon2id_known_answer() {
+        let kdf = Kdf::argon2id(3, 16, 4).unwrap();
+        let source_key = SourceKey::new("user@example.com", "correct horse battery staple", &kdf);
+        assert_eq!(
+            source_key.encode(),
+            "XbOY1hcoh5H7e3zxxCrQAKzsWL3ixb7RyibchVbzMeA="
+        );
+    }
+}
@@ -7,8 +7,12 @@ pub use self::aes::{
     AesCbc128HmacSha256, AesCbc256, AesCbc256HmacSha256, AesCbc256ParseError,
     AesCbcHmacSha256DecryptionError, AesCbcHmacSha256ParseError,
 };
+#[cfg(feature = "tokio")]
+pub use self::stream::StreamDecryptionError;
 
 mod aes;
+#[cfg(feature = "tokio")]
+mod stream;
 
 /// Parse error for [`SymmetricEncryption`].
 #[derive(Debug, Clone, Error)]
@@ -17,6 +21,8 @@ pub enum ParseError {
     ParseEncryptionType(#[from] ParseIntError),
     #[error("invalid encryption type (expected one of `{:?}`, found `{}`)", .expected, .found)]
     InvalidEncryptionType { expected: [usize; 3], found: usize },
+    #[error("encryption type not found")]
+    EncryptionTypeNotFound,
     #[error("AesCbc256 parse error")]
     AesCbc256(AesCbc256ParseError),
     #[error("AesCbc128HmacSha256 parse error")]
@@ -89,6 +95,37 @@ impl Parse for SymmetricEncryption {
     }
 }
 
+impl SymmetricEncryption {
+    /// Parses the binary layout that is used for encrypted files (e.g. attachments).
+    pub fn from_bytes(value: &[u8]) -> Result<Self, ParseError> {
+        match value.first() {
+            Some(0) => Ok(Self::AesCbc256(
+                AesCbc256::from_bytes(value).map_err(ParseError::AesCbc256)?,
+            )),
+            Some(1) => Ok(Self::AesCbc128HmacSha256(
+                AesCbc128HmacSha256::from_bytes(value).map_err(ParseError::AesCbc128HmacSha256)?,
+            )),
+            Some(2) => Ok(Self::AesCbc256HmacSha256(
+                AesCbc256HmacSha256::from_bytes(value).map_err(ParseError::AesCbc256HmacSha256)?,
+            )),
+            Some(&ty) => Err(ParseError::InvalidEncryptionType {
+                expected: [0, 1, 2],
+                found: ty.into(),
+            }),
+            None => Err(ParseError::EncryptionTypeNotFound),
+        }
+    }
+
+    /// Returns the binary layout that is used for encrypted files (e.g. attachments).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::AesCbc256(v) => v.to_bytes(),
+            Self::AesCbc128HmacSha256(v) => v.to_bytes(),
+            Self::AesCbc256HmacSha256(v) => v.to_bytes(),
+        }
+    }
+}
+
 impl Encrypt for SymmetricEncryption {
     type Params = SymmetricKey;
     fn encrypt<P: AsRef<[u8]>>(plaintext: P, params: &Self::Params) -> Self {
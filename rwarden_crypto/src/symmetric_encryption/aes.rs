@@ -1,11 +1,38 @@
 use crate::{Decrypt, Encrypt, Parse};
-use aes::{Aes128, Aes256};
+use aes::{Aes128, Aes256, BlockCipher, BlockDecrypt, BlockEncrypt, NewBlockCipher};
 use block_modes::{block_padding::Pkcs7, BlockMode, BlockModeError, Cbc};
 use generic_array::GenericArray;
 use hmac::{Hmac, Mac, NewMac};
 use sha2::Sha256;
 use std::{convert::TryInto, fmt, num::ParseIntError};
 use thiserror::Error;
+use zeroize::Zeroize;
+
+/// Decrypts the ciphertext in place.
+///
+/// Unlike [`BlockMode::decrypt_vec`], this makes sure that no partially decrypted data is left in
+/// memory if the decryption fails.
+fn decrypt_cbc<C>(enc: &[u8], iv: &[u8; 16], ciphertext: &[u8]) -> Result<Vec<u8>, BlockModeError>
+where
+    C: BlockCipher + BlockEncrypt + BlockDecrypt + NewBlockCipher,
+{
+    let mut buffer = ciphertext.to_vec();
+    let result =
+        Cbc::<C, Pkcs7>::new_fix(GenericArray::from_slice(enc), GenericArray::from_slice(iv))
+            .decrypt(&mut buffer)
+            .map(|plaintext| plaintext.len());
+    match result {
+        Ok(len) => {
+            buffer[len..].zeroize();
+            buffer.truncate(len);
+            Ok(buffer)
+        }
+        Err(e) => {
+            buffer.zeroize();
+            Err(e)
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AesCbc256 {
@@ -61,6 +88,35 @@ impl Parse for AesCbc256 {
     }
 }
 
+impl AesCbc256 {
+    /// Parses the binary layout that is used for encrypted files.
+    ///
+    /// The layout is the encryption type (one byte), the initialization vector and the ciphertext.
+    pub fn from_bytes(value: &[u8]) -> Result<Self, AesCbc256ParseError> {
+        let (&ty, value) = value.split_first().ok_or(AesCbc256ParseError::IvNotFound)?;
+        if ty != 0 {
+            return Err(AesCbc256ParseError::InvalidEncryptionType {
+                expected: 0,
+                found: ty.into(),
+            });
+        }
+        if value.len() < 16 {
+            return Err(AesCbc256ParseError::IvNotFound);
+        }
+        let (iv, ciphertext) = value.split_at(16);
+        Ok(Self {
+            // `unwrap` is safe here because the length is checked above
+            iv: iv.try_into().unwrap(),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    /// Returns the binary layout that is used for encrypted files.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&[0], &self.iv[..], &self.ciphertext].concat()
+    }
+}
+
 impl Encrypt for AesCbc256 {
     /// The encryption key.
     type Params = [u8; 32];
@@ -81,12 +137,7 @@ impl Decrypt for AesCbc256 {
     type Params = [u8; 32];
     type Error = BlockModeError;
     fn decrypt(&self, params: &Self::Params) -> Result<Vec<u8>, Self::Error> {
-        let enc = params;
-        Cbc::<Aes128, Pkcs7>::new_fix(
-            GenericArray::from_slice(enc),
-            GenericArray::from_slice(&self.iv),
-        )
-        .decrypt_vec(&self.ciphertext)
+        decrypt_cbc::<Aes256>(params, &self.iv, &self.ciphertext)
     }
 }
 
@@ -135,6 +186,60 @@ pub struct AesCbc128HmacSha256 {
     pub ciphertext: Vec<u8>,
 }
 
+impl AesCbc128HmacSha256 {
+    /// Parses the binary layout that is used for encrypted files.
+    ///
+    /// The layout is the encryption type (one byte), the initialization vector, the MAC and the
+    /// ciphertext.
+    pub fn from_bytes(value: &[u8]) -> Result<Self, AesCbcHmacSha256ParseError> {
+        let (iv, mac, ciphertext) = split_hmac_bytes(value, 1)?;
+        Ok(Self {
+            iv,
+            mac,
+            ciphertext,
+        })
+    }
+
+    /// Returns the binary layout that is used for encrypted files.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&[1], &self.iv[..], &self.mac[..], &self.ciphertext].concat()
+    }
+}
+
+/// The initialization vector, the MAC and the ciphertext.
+type HmacParts = ([u8; 16], [u8; 32], Vec<u8>);
+
+/// Splits the binary layout of [`AesCbc128HmacSha256`] and [`AesCbc256HmacSha256`] into the
+/// initialization vector, the MAC and the ciphertext.
+fn split_hmac_bytes(
+    value: &[u8],
+    expected_ty: u8,
+) -> Result<HmacParts, AesCbcHmacSha256ParseError> {
+    let (&ty, value) = value
+        .split_first()
+        .ok_or(AesCbcHmacSha256ParseError::IvNotFound)?;
+    if ty != expected_ty {
+        return Err(AesCbcHmacSha256ParseError::InvalidEncryptionType {
+            expected: expected_ty.into(),
+            found: ty.into(),
+        });
+    }
+    if value.len() < 16 {
+        return Err(AesCbcHmacSha256ParseError::IvNotFound);
+    }
+    let (iv, value) = value.split_at(16);
+    if value.len() < 32 {
+        return Err(AesCbcHmacSha256ParseError::MacNotFound);
+    }
+    let (mac, ciphertext) = value.split_at(32);
+    // `unwrap` is safe here because the lengths are checked above
+    Ok((
+        iv.try_into().unwrap(),
+        mac.try_into().unwrap(),
+        ciphertext.to_vec(),
+    ))
+}
+
 impl Parse for AesCbc128HmacSha256 {
     type Error = AesCbcHmacSha256ParseError;
     fn parse<S: AsRef<str>>(value: S) -> Result<Self, Self::Error> {
@@ -206,11 +311,7 @@ impl Decrypt for AesCbc128HmacSha256 {
         new_mac.update(&self.iv);
         new_mac.update(&self.ciphertext);
         new_mac.verify(&self.mac)?;
-        Ok(Cbc::<Aes128, Pkcs7>::new_fix(
-            GenericArray::from_slice(enc),
-            GenericArray::from_slice(&self.iv),
-        )
-        .decrypt_vec(&self.ciphertext)?)
+        Ok(decrypt_cbc::<Aes128>(enc, &self.iv, &self.ciphertext)?)
     }
 }
 
@@ -268,6 +369,26 @@ impl Parse for AesCbc256HmacSha256 {
     }
 }
 
+impl AesCbc256HmacSha256 {
+    /// Parses the binary layout that is used for encrypted files.
+    ///
+    /// The layout is the encryption type (one byte), the initialization vector, the MAC and the
+    /// ciphertext.
+    pub fn from_bytes(value: &[u8]) -> Result<Self, AesCbcHmacSha256ParseError> {
+        let (iv, mac, ciphertext) = split_hmac_bytes(value, 2)?;
+        Ok(Self {
+            iv,
+            mac,
+            ciphertext,
+        })
+    }
+
+    /// Returns the binary layout that is used for encrypted files.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&[2], &self.iv[..], &self.mac[..], &self.ciphertext].concat()
+    }
+}
+
 impl Encrypt for AesCbc256HmacSha256 {
     /// The encryption key and the MAC key.
     type Params = ([u8; 32], [u8; 32]);
@@ -301,11 +422,7 @@ impl Decrypt for AesCbc256HmacSha256 {
         new_mac.update(&self.iv);
         new_mac.update(&self.ciphertext);
         new_mac.verify(&self.mac)?;
-        Ok(Cbc::<Aes256, Pkcs7>::new_fix(
-            GenericArray::from_slice(enc),
-            GenericArray::from_slice(&self.iv),
-        )
-        .decrypt_vec(&self.ciphertext)?)
+        Ok(decrypt_cbc::<Aes256>(enc, &self.iv, &self.ciphertext)?)
     }
 }
 
@@ -317,3 +434,30 @@ impl fmt::Display for AesCbc256HmacSha256 {
         f.write_fmt(format_args!("2.{}|{}|{}", iv, ciphertext, mac))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SymmetricEncryptedBytes, SymmetricEncryption, SymmetricKey};
+
+    #[test]
+    fn aes_cbc_256_round_trip() {
+        let key = [7; 32];
+        let encrypted = AesCbc256::encrypt("foo", &key).to_string();
+        assert!(encrypted.starts_with("0."));
+        let decrypted = AesCbc256::parse(encrypted).unwrap().decrypt(&key).unwrap();
+        assert_eq!(decrypted, b"foo");
+    }
+
+    #[test]
+    fn encrypted_file_bytes() {
+        let key = SymmetricKey::generate();
+        let encrypted = SymmetricEncryptedBytes::encrypt(b"data", &key);
+        let bytes = encrypted.0.to_bytes();
+        assert_eq!(bytes[0], 2);
+        let parsed = SymmetricEncryption::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, encrypted.0);
+        assert!(SymmetricEncryption::from_bytes(&bytes[..20]).is_err());
+        assert!(SymmetricEncryption::from_bytes(&[]).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use super::{AesCbc256HmacSha256, AesCbcHmacSha256DecryptionError, AesCbcHmacSha256ParseError};
+use aes::Aes256;
+use block_modes::{block_padding::NoPadding, BlockMode, BlockModeError, Cbc};
+use generic_array::GenericArray;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::convert::TryInto;
+use std::io::{self, SeekFrom};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use zeroize::Zeroizing;
+
+/// The size of the buffer that the data is processed in. This is a multiple of the block size.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+const BLOCK_SIZE: usize = 16;
+
+/// The length of the encryption type, the initialization vector and the MAC.
+const HEADER_LENGTH: usize = 1 + 16 + 32;
+
+/// Decryption error for [`AesCbc256HmacSha256::decrypt_stream`].
+#[derive(Debug, Error)]
+pub enum StreamDecryptionError {
+    #[error("failed to read or write data")]
+    Io(#[from] io::Error),
+    #[error("AesCbc256HmacSha256 parse error")]
+    Parse(#[from] AesCbcHmacSha256ParseError),
+    #[error("AesCbc256HmacSha256 decryption error")]
+    Decryption(#[from] AesCbcHmacSha256DecryptionError),
+}
+
+impl From<BlockModeError> for StreamDecryptionError {
+    fn from(e: BlockModeError) -> Self {
+        Self::Decryption(e.into())
+    }
+}
+
+fn new_cbc(enc: &[u8; 32], iv: &[u8; 16]) -> Cbc<Aes256, NoPadding> {
+    Cbc::new_fix(GenericArray::from_slice(enc), GenericArray::from_slice(iv))
+}
+
+fn new_hmac(mac: &[u8; 32], iv: &[u8; 16]) -> Hmac<Sha256> {
+    // `unwrap` is safe here because HMAC accepts keys of any length
+    let mut hmac = Hmac::<Sha256>::new_from_slice(mac).unwrap();
+    hmac.update(iv);
+    hmac
+}
+
+/// Reads until the buffer is full or the end of the reader is reached.
+async fn read_full<R>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = 0;
+    while len < buffer.len() {
+        match reader.read(&mut buffer[len..]).await? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+impl AesCbc256HmacSha256 {
+    /// Encrypts the data of the reader and writes it to the writer in the binary layout that is
+    /// used for encrypted files (see [`AesCbc256HmacSha256::to_bytes`]).
+    ///
+    /// The MAC precedes the ciphertext, so the writer is seeked back to write the MAC after the
+    /// ciphertext was written. Returns the number of bytes that were written. This requires the
+    /// `tokio` feature.
+    pub async fn encrypt_stream<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        params: &([u8; 32], [u8; 32]),
+    ) -> io::Result<u64>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + AsyncSeek + Unpin,
+    {
+        let (enc, mac) = params;
+        let iv = crate::generate_iv();
+        let mut cbc = new_cbc(enc, &iv);
+        let mut hmac = new_hmac(mac, &iv);
+
+        let start = writer.seek(SeekFrom::Current(0)).await?;
+        writer.write_all(&[2]).await?;
+        writer.write_all(&iv).await?;
+        // The MAC is not known yet
+        writer.write_all(&[0; 32]).await?;
+
+        let mut buffer = Zeroizing::new(vec![0; BUFFER_SIZE]);
+        let mut ciphertext_length = 0;
+        loop {
+            let len = read_full(reader, &mut buffer).await?;
+            let is_last = len < buffer.len();
+            let len = if is_last {
+                // PKCS#7 padding, a whole block is added if the length is a multiple of the
+                // block size
+                let padding_length = BLOCK_SIZE - len % BLOCK_SIZE;
+                buffer[len..len + padding_length].fill(padding_length as u8);
+                len + padding_length
+            } else {
+                len
+            };
+            for block in buffer[..len].chunks_exact_mut(BLOCK_SIZE) {
+                cbc.encrypt_blocks(std::slice::from_mut(GenericArray::from_mut_slice(block)));
+            }
+            hmac.update(&buffer[..len]);
+            writer.write_all(&buffer[..len]).await?;
+            ciphertext_length += len as u64;
+            if is_last {
+                break;
+            }
+        }
+
+        let mac = hmac.finalize().into_bytes();
+        writer.seek(SeekFrom::Start(start + 17)).await?;
+        writer.write_all(&mac).await?;
+        let length = HEADER_LENGTH as u64 + ciphertext_length;
+        writer.seek(SeekFrom::Start(start + length)).await?;
+        writer.flush().await?;
+        Ok(length)
+    }
+
+    /// Decrypts data in the binary layout that is used for encrypted files (see
+    /// [`AesCbc256HmacSha256::from_bytes`]) and writes the plaintext to the writer.
+    ///
+    /// The data is read twice: The MAC is verified first and nothing is written to the writer if
+    /// the verification fails. Afterwards the reader is seeked back and the data is decrypted.
+    /// Returns the number of bytes that were written. This requires the `tokio` feature.
+    ///
+    /// If the data changes between the two passes, the writer may already contain unauthenticated
+    /// plaintext when the modification is detected. The MAC is verified again before the last
+    /// block is written, so an error is returned in that case, but all output that was written
+    /// before must be discarded by the caller if an error is returned.
+    pub async fn decrypt_stream<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        params: &([u8; 32], [u8; 32]),
+    ) -> Result<u64, StreamDecryptionError>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let (enc, mac_key) = params;
+        let mut header = [0; HEADER_LENGTH];
+        let header_length = read_full(reader, &mut header).await?;
+        match header_length {
+            0 => return Err(AesCbcHmacSha256ParseError::IvNotFound.into()),
+            _ if header[0] != 2 => {
+                return Err(AesCbcHmacSha256ParseError::InvalidEncryptionType {
+                    expected: 2,
+                    found: header[0].into(),
+                }
+                .into())
+            }
+            1..=16 => return Err(AesCbcHmacSha256ParseError::IvNotFound.into()),
+            _ if header_length < HEADER_LENGTH => {
+                return Err(AesCbcHmacSha256ParseError::MacNotFound.into())
+            }
+            _ => {}
+        }
+        // `unwrap` is safe here because the lengths of the slices are correct
+        let iv: [u8; 16] = header[1..17].try_into().unwrap();
+        let mac: [u8; 32] = header[17..].try_into().unwrap();
+        let ciphertext_start = reader.seek(SeekFrom::Current(0)).await?;
+
+        let mut buffer = Zeroizing::new(vec![0; BUFFER_SIZE]);
+        let mut hmac = new_hmac(mac_key, &iv);
+        let mut ciphertext_length = 0;
+        loop {
+            let len = reader.read(&mut buffer).await?;
+            if len == 0 {
+                break;
+            }
+            hmac.update(&buffer[..len]);
+            ciphertext_length += len as u64;
+        }
+        hmac.verify(&mac)
+            .map_err(AesCbcHmacSha256DecryptionError::from)?;
+        if ciphertext_length == 0 || ciphertext_length % BLOCK_SIZE as u64 != 0 {
+            return Err(BlockModeError.into());
+        }
+
+        reader.seek(SeekFrom::Start(ciphertext_start)).await?;
+        let mut reader = reader.take(ciphertext_length);
+        let mut cbc = new_cbc(enc, &iv);
+        let mut hmac = new_hmac(mac_key, &iv);
+        let mut remaining = ciphertext_length;
+        let mut plaintext_length = 0;
+        loop {
+            let len = read_full(&mut reader, &mut buffer).await?;
+            remaining -= len as u64;
+            if remaining > 0 && len < buffer.len() {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            hmac.update(&buffer[..len]);
+            for block in buffer[..len].chunks_exact_mut(BLOCK_SIZE) {
+                cbc.decrypt_blocks(std::slice::from_mut(GenericArray::from_mut_slice(block)));
+            }
+            if remaining > 0 {
+                writer.write_all(&buffer[..len]).await?;
+                plaintext_length += len as u64;
+                continue;
+            }
+            hmac.verify(&mac)
+                .map_err(AesCbcHmacSha256DecryptionError::from)?;
+            // PKCS#7 padding
+            let padding_length = buffer[len - 1] as usize;
+            if padding_length == 0
+                || padding_length > BLOCK_SIZE
+                || buffer[len - padding_length..len]
+                    .iter()
+                    .any(|v| *v as usize != padding_length)
+            {
+                return Err(BlockModeError.into());
+            }
+            let len = len - padding_length;
+            writer.write_all(&buffer[..len]).await?;
+            plaintext_length += len as u64;
+            break;
+        }
+        writer.flush().await?;
+        Ok(plaintext_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decrypt, SymmetricEncryption, SymmetricKey};
+    use rand::RngCore;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn encrypt_and_decrypt_stream() {
+        let key = SymmetricKey::generate();
+        let params = (key.enc, key.mac.unwrap());
+        for len in [0, 15, 16, BUFFER_SIZE, 200_000] {
+            let mut data = vec![0; len];
+            rand::thread_rng().fill_bytes(&mut data);
+
+            let mut encrypted = Cursor::new(Vec::new());
+            let length =
+                AesCbc256HmacSha256::encrypt_stream(&mut data.as_slice(), &mut encrypted, &params)
+                    .await
+                    .unwrap();
+            let mut encrypted = encrypted.into_inner();
+            assert_eq!(length, encrypted.len() as u64);
+            let decrypted = SymmetricEncryption::from_bytes(&encrypted)
+                .unwrap()
+                .decrypt(&key)
+                .unwrap();
+            assert_eq!(decrypted, data);
+
+            let mut decrypted = Vec::new();
+            AesCbc256HmacSha256::decrypt_stream(
+                &mut Cursor::new(&encrypted),
+                &mut decrypted,
+                &params,
+            )
+            .await
+            .unwrap();
+            assert_eq!(decrypted, data);
+
+            let last = encrypted.len() - 1;
+            encrypted[last] ^= 1;
+            let mut decrypted = Vec::new();
+            let error = AesCbc256HmacSha256::decrypt_stream(
+                &mut Cursor::new(&encrypted),
+                &mut decrypted,
+                &params,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(error, StreamDecryptionError::Decryption(_)));
+            assert!(decrypted.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn decrypt_stream_modified_early_chunk() {
+        let key = SymmetricKey::generate();
+        let params = (key.enc, key.mac.unwrap());
+        let data = vec![7; 3 * BUFFER_SIZE];
+        let mut encrypted = Cursor::new(Vec::new());
+        AesCbc256HmacSha256::encrypt_stream(&mut data.as_slice(), &mut encrypted, &params)
+            .await
+            .unwrap();
+        let mut encrypted = encrypted.into_inner();
+        encrypted[HEADER_LENGTH + 10] ^= 1;
+
+        let mut decrypted = Vec::new();
+        let error = AesCbc256HmacSha256::decrypt_stream(
+            &mut Cursor::new(&encrypted),
+            &mut decrypted,
+            &params,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, StreamDecryptionError::Decryption(_)));
+        assert!(decrypted.is_empty());
+    }
+}
@@ -1,9 +1,10 @@
-use crate::symmetric_encryption::{AesCbcHmacSha256DecryptionError, SymmetricEncryption};
-use crate::{Decrypt, SourceKey, SymmetricEncryptedBytes};
+use crate::symmetric_encryption::{self, AesCbcHmacSha256DecryptionError, SymmetricEncryption};
+use crate::{Decrypt, Encrypt, GenericEncryptedBytes, SourceKey, SymmetricEncryptedBytes};
 use block_modes::BlockModeError;
 use rand::{rngs::OsRng, RngCore};
-use std::convert::TryInto;
+use std::{convert::TryInto, fmt};
 use thiserror::Error;
+use zeroize::Zeroize;
 
 #[derive(Debug, Clone, Error)]
 pub enum SymmetricKeyError {
@@ -17,7 +18,10 @@ pub enum SymmetricKeyError {
     AesCbc256HmacSha256Decryption(#[from] AesCbcHmacSha256DecryptionError),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A key used for symmetric encryption.
+///
+/// The key is zeroized when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
 pub struct SymmetricKey {
     pub enc: [u8; 32],
     pub mac: Option<[u8; 32]>,
@@ -30,11 +34,11 @@ impl SymmetricKey {
     ) -> Result<Self, SymmetricKeyError> {
         match &protected_symmetric_key.0 {
             SymmetricEncryption::AesCbc256(v) => {
-                let enc_key = v.decrypt(&source_key.0)?;
+                let mut enc_key = v.decrypt(&source_key.0)?;
+                let enc = enc_key.as_slice().try_into();
+                enc_key.zeroize();
                 Ok(Self {
-                    enc: enc_key
-                        .try_into()
-                        .map_err(|_| SymmetricKeyError::InvalidLength)?,
+                    enc: enc.map_err(|_| SymmetricKeyError::InvalidLength)?,
                     mac: None,
                 })
             }
@@ -43,19 +47,37 @@ impl SymmetricKey {
             }
             SymmetricEncryption::AesCbc256HmacSha256(v) => {
                 let (enc, mac) = source_key.expand();
-                let keys = v.decrypt(&(enc, mac))?;
-                if keys.len() != 64 {
-                    return Err(SymmetricKeyError::InvalidLength);
-                }
-                Ok(Self {
-                    enc: keys[0..32].try_into().unwrap(),
-                    mac: Some(keys[32..64].try_into().unwrap()),
-                })
+                let mut keys = v.decrypt(&(enc, mac))?;
+                let key = if keys.len() == 64 {
+                    Ok(Self {
+                        enc: keys[0..32].try_into().unwrap(),
+                        mac: Some(keys[32..64].try_into().unwrap()),
+                    })
+                } else {
+                    Err(SymmetricKeyError::InvalidLength)
+                };
+                keys.zeroize();
+                key
             }
         }
     }
 
-    pub(crate) fn generate() -> Self {
+    /// Creates a symmetric key from the encryption key and the MAC key (64 bytes).
+    ///
+    /// This is the format of the keys of organizations and attachments.
+    pub fn from_bytes(value: &[u8]) -> Result<Self, SymmetricKeyError> {
+        if value.len() != 64 {
+            return Err(SymmetricKeyError::InvalidLength);
+        }
+        // `unwrap` is safe here because the length is checked above
+        Ok(Self {
+            enc: value[0..32].try_into().unwrap(),
+            mac: Some(value[32..64].try_into().unwrap()),
+        })
+    }
+
+    /// Generates a new random symmetric key.
+    pub fn generate() -> Self {
         let mut enc = [0; 32];
         OsRng.fill_bytes(&mut enc);
         let mut mac = [0; 32];
@@ -65,4 +87,41 @@ impl SymmetricKey {
             mac: Some(mac),
         }
     }
+
+    /// Encrypts the symmetric key with the given source key.
+    ///
+    /// The result is the protected symmetric key that is stored on the server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symmetric key has no MAC key.
+    pub fn protect(
+        &self,
+        source_key: &SourceKey,
+    ) -> GenericEncryptedBytes<symmetric_encryption::AesCbc256HmacSha256> {
+        let mac = self.mac.expect("symmetric key has no MAC key");
+        let mut data = [self.enc, mac].concat();
+        let protected_symmetric_key =
+            symmetric_encryption::AesCbc256HmacSha256::encrypt(&data, &source_key.expand());
+        data.zeroize();
+        GenericEncryptedBytes(protected_symmetric_key)
+    }
+}
+
+impl Drop for SymmetricKey {
+    fn drop(&mut self) {
+        self.enc.zeroize();
+        if let Some(mac) = &mut self.mac {
+            mac.zeroize();
+        }
+    }
+}
+
+impl fmt::Debug for SymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SymmetricKey")
+            .field("enc", &"<redacted>")
+            .field("mac", &self.mac.map(|_| "<redacted>"))
+            .finish()
+    }
 }